@@ -1,7 +1,7 @@
 use lavalink_client_rs::manager::{LavalinkManager, LavalinkManagerOptions};
 use lavalink_client_rs::node::LavalinkNodeOptions;
+use lavalink_client_rs::types::events::ManagerEvent;
 use tracing::{info, error};
-use tracing_subscriber;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -16,7 +16,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ..Default::default()
     };
     
-    let (mut manager, mut rx) = LavalinkManager::new(options);
+    let manager = LavalinkManager::new(options);
+    // Subscribe before adding nodes so no event is missed
+    let mut rx = manager.subscribe();
 
     // Create Node Options for a local Lavalink server
     let node_options = LavalinkNodeOptions {
@@ -27,6 +29,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         secure: Some(false),
         request_timeout: Some(10000),
         session_id: None,
+        ..Default::default()
     };
 
     info!("Adding node...");
@@ -36,14 +39,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let search_manager = manager; 
     
     tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            info!("Received Event: {:?}", msg);
-            match msg {
-                lavalink_client_rs::types::events::LavalinkMessage::Ready { session_id, .. } => {
-                    info!("Node is ready with session_id: {}", session_id);
+        while let Ok(event) = rx.recv().await {
+            match event {
+                ManagerEvent::NodeRaw { .. } | ManagerEvent::Debug { .. } => {},
+                ManagerEvent::NodeReady { node_id, session_id, .. } => {
+                    info!("Node {} is ready with session_id: {}", node_id, session_id);
                     
                     // We can try to search for a track once the node is ready
-                    if let Some(node) = search_manager.node_manager.get_node("LocalNode") {
+                    if let Some(node) = search_manager.node_manager.get_node(&node_id) {
                         info!("Searching for 'ytsearch:hello'...");
                         match node.search("ytsearch:hello").await {
                             Ok(res) => {
@@ -57,7 +60,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         error!("Node not found in node manager");
                     }
                 },
-                _ => {}
+                event => info!("Received Event: {:?}", event),
             }
         }
     });
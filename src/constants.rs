@@ -408,6 +408,19 @@ lazy_static! {
     };
 }
 
+/// Names of plugins the client checks for in the node's info
+pub struct LavalinkPlugins;
+
+impl LavalinkPlugins {
+    pub const DUNCTE_BOT: &'static str = "DuncteBot-plugin";
+    pub const GOOGLE_CLOUD_TTS: &'static str = "tts-plugin";
+    pub const LAVA_SEARCH: &'static str = "lavasearch-plugin";
+    pub const LAVA_SRC: &'static str = "lavasrc-plugin";
+    pub const JIOSAAVN: &'static str = "jiosaavn-plugin";
+    pub const SPONSOR_BLOCK: &'static str = "sponsorblock-plugin";
+    pub const LAVA_LYRICS: &'static str = "lavalyrics-plugin";
+}
+
 pub struct RecommendationsStrings;
 
 impl RecommendationsStrings {
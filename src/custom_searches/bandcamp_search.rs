@@ -54,7 +54,7 @@ pub async fn bandcamp_search(
                             for item in results {
                                 if item.r#type.as_deref() == Some("t") {
                                     let uri = item.url.or(item.uri);
-                                    let id = item.id.map(|x| x.to_string()).or_else(|| uri.clone().and_then(|u| u.split('/').last().map(String::from)));
+                                    let _id = item.id.map(|x| x.to_string()).or_else(|| uri.clone().and_then(|u| u.split('/').next_back().map(String::from)));
                                     
                                     let info = UnresolvedTrackInfo {
                                         title: item.name.unwrap_or_default(),
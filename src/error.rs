@@ -0,0 +1,90 @@
+use crate::types::node::NodeCapability;
+use crate::types::utils::InvalidLavalinkRestRequest;
+use std::fmt;
+
+/// Errors returned by the client
+#[derive(Debug)]
+pub enum LavalinkError {
+    /// The node has no session yet, it did not receive the ready op
+    NodeNotReady { node_id: String },
+    /// No node with this id is registered
+    NodeNotFound { node_id: String },
+    /// A node with this id is already registered
+    NodeAlreadyExists { node_id: String },
+    /// No connected node is available for a player
+    NoAvailableNode,
+    /// The node answered with an unsuccessful status, `body` is Lavalink's error response when it sent one
+    Http { status: u16, body: Option<InvalidLavalinkRestRequest> },
+    /// The request could not be sent or its response could not be read
+    Request(reqwest::Error),
+    /// The request timed out
+    Timeout,
+    /// A response or payload could not be deserialized
+    Decode(String),
+    /// No player exists for this guild
+    PlayerNotFound { guild_id: String },
+    /// A player already exists for this guild
+    PlayerAlreadyExists { guild_id: String },
+    /// The search query or source is not valid
+    InvalidQuery(String),
+    /// An argument is missing or not valid
+    InvalidArgument(String),
+    /// The operation is not possible in the current state, e.g. pausing a paused player
+    InvalidState(String),
+    /// The node's server doesn't support the operation, e.g. mixer layers on Lavalink
+    Unsupported { node_id: String, capability: NodeCapability },
+}
+
+impl fmt::Display for LavalinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NodeNotReady { node_id } => write!(f, "Node {} is not ready yet: No Session ID", node_id),
+            Self::NodeNotFound { node_id } => write!(f, "Node {} not found", node_id),
+            Self::NodeAlreadyExists { node_id } => write!(f, "Node with id {} already exists", node_id),
+            Self::NoAvailableNode => write!(f, "No available Lavalink node"),
+            Self::Http { status, body: Some(body) } => write!(
+                f,
+                "Lavalink request to {} failed ({} {}): {}",
+                body.path,
+                status,
+                body.error,
+                body.message.as_deref().unwrap_or("no message")
+            ),
+            Self::Http { status, body: None } => write!(f, "Lavalink request failed with status {}", status),
+            Self::Request(e) => write!(f, "Request failed: {}", e),
+            Self::Timeout => write!(f, "Request timed out"),
+            Self::Decode(e) => write!(f, "Failed to decode the response: {}", e),
+            Self::PlayerNotFound { guild_id } => write!(f, "Player for guild {} not found", guild_id),
+            Self::PlayerAlreadyExists { guild_id } => write!(f, "Player for guild {} already exists", guild_id),
+            Self::InvalidQuery(message) | Self::InvalidArgument(message) | Self::InvalidState(message) => f.write_str(message),
+            Self::Unsupported { node_id, capability } => write!(f, "Node {} does not support {}", node_id, capability.as_str()),
+        }
+    }
+}
+
+impl std::error::Error for LavalinkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for LavalinkError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::Timeout
+        } else if e.is_decode() {
+            Self::Decode(e.to_string())
+        } else {
+            Self::Request(e)
+        }
+    }
+}
+
+impl From<serde_json::Error> for LavalinkError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Decode(e.to_string())
+    }
+}
@@ -1,4 +1,5 @@
 pub mod types;
+pub mod error;
 pub mod utils;
 pub mod player;
 pub mod node;
@@ -1,10 +1,22 @@
-use crate::node_manager::NodeManager;
-use crate::utils::ManagerUtils;
+use crate::node::LavalinkNode;
+use crate::node_manager::{DefaultSessionStore, NodeManager, NodeSortStrategy};
+use crate::utils::{ManagerUtils, voice_region_from_endpoint, DEFAULT_SEARCH_PLATFORM};
 use crate::player::Player;
-use crate::types::player::PlayerOptions;
-use crate::types::events::LavalinkMessage;
+use crate::constants::{DebugEvents, DestroyReasons};
+use crate::error::LavalinkError;
+use crate::types::node::SessionStore;
+use crate::types::player::{LavalinkPlayerVoiceOptions, PlayerOptions, PlayOptions, RepeatMode};
+use crate::types::events::{Exception, LavalinkMessage, LavalinkPlayer, LavalinkPlayerState, ManagerEvent, NodeMessage, PlayerEvent};
+use crate::types::manager::{AdvancedOptions, ManagerPlayerOptions};
+use crate::types::queue::QueueTrack;
+use crate::types::track::{LavalinkTrack, Track};
+use serde_json::Value;
 use std::collections::HashMap;
-use tokio::sync::{RwLock, mpsc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedRwLockWriteGuard, RwLock, RwLockWriteGuard, broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
 
 #[derive(Debug, Clone)]
 pub struct LavalinkManagerOptions {
@@ -13,6 +25,17 @@ pub struct LavalinkManagerOptions {
     pub emit_new_songs_only: bool,
     pub user_id: String,
     pub client_name: String,
+    pub node_sort_strategy: NodeSortStrategy,
+    /// Move the players of a disconnected node to the least used remaining node
+    pub auto_move: bool,
+    /// Move a player to a node serving its new voice region when the region changes in a voice server update
+    pub move_on_region_change: bool,
+    /// Enables session resuming, disabled when `None`
+    pub resume: Option<ResumeOptions>,
+    /// Options applied to every player, e.g. the volume decrementer
+    pub player_options: ManagerPlayerOptions,
+    /// Advanced options of the players, e.g. the max track duration of the filter fix
+    pub advanced_options: AdvancedOptions,
 }
 
 impl Default for LavalinkManagerOptions {
@@ -23,51 +46,135 @@ impl Default for LavalinkManagerOptions {
             emit_new_songs_only: false,
             user_id: String::new(),
             client_name: "lavalink-client-rs".to_string(),
+            node_sort_strategy: NodeSortStrategy::default(),
+            auto_move: false,
+            move_on_region_change: false,
+            resume: None,
+            player_options: ManagerPlayerOptions::default(),
+            advanced_options: AdvancedOptions::default(),
         }
     }
 }
 
+/// Session resuming: Lavalink keeps a node's players alive for `timeout` seconds after the client disconnected
+#[derive(Clone)]
+pub struct ResumeOptions {
+    /// Seconds Lavalink waits for the client to resume the session
+    pub timeout: u64,
+    /// Where session ids are persisted, use a persistent store like [`FileSessionStore`](crate::node_manager::FileSessionStore) to resume after a restart
+    pub store: Arc<dyn SessionStore>,
+}
+
+impl std::fmt::Debug for ResumeOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResumeOptions")
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl Default for ResumeOptions {
+    fn default() -> Self {
+        Self {
+            timeout: 60,
+            store: Arc::new(DefaultSessionStore::new()),
+        }
+    }
+}
+
+/// The players stored by the manager, keyed by guild id.
+pub type PlayerMap = Arc<RwLock<HashMap<String, Arc<RwLock<Player>>>>>;
+
+/// How many events a subscriber may lag behind before it starts missing events
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
 pub struct LavalinkManager {
     pub options: LavalinkManagerOptions,
     pub node_manager: NodeManager,
     pub utils: ManagerUtils,
-    pub players: RwLock<HashMap<String, Player>>, 
-    // Usually an event bus / emitter here, for now we will just process to stdout.
+    pub players: PlayerMap,
+    pub event_sender: broadcast::Sender<ManagerEvent>,
+    /// Stops the manager's background tasks once it's dropped
+    shutdown: CancellationToken,
 }
 
 impl LavalinkManager {
-    pub fn new(options: LavalinkManagerOptions) -> (Self, mpsc::Receiver<LavalinkMessage>) {
-        let (tx, rx) = mpsc::channel(100);
-        
-        let manager = Self {
+    /// Creates the manager and spawns its event dispatcher, so it must be called from within a tokio runtime.
+    /// The dispatcher stops when the manager is dropped.
+    /// Use [`LavalinkManager::subscribe`] to receive events.
+    pub fn new(options: LavalinkManagerOptions) -> Self {
+        let (node_tx, node_rx) = mpsc::channel(100);
+        let (event_sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
+        let utils = ManagerUtils::new(DEFAULT_SEARCH_PLATFORM.to_string());
+        let players: PlayerMap = Arc::new(RwLock::new(HashMap::new()));
+
+        let session_store = options.resume.as_ref().map(|resume| resume.store.clone());
+        let node_manager = NodeManager::new(node_tx, options.node_sort_strategy, session_store);
+
+        let shutdown = CancellationToken::new();
+        let dispatcher = EventDispatcher {
             options: options.clone(),
-            node_manager: NodeManager::new(tx),
-            utils: ManagerUtils::new("ytsearch".to_string()),
-            players: RwLock::new(HashMap::new()),
+            node_manager: node_manager.clone(),
+            players: players.clone(),
+            utils: utils.clone(),
+            event_sender: event_sender.clone(),
+            shutdown: shutdown.clone(),
         };
+        tokio::spawn(Arc::new(dispatcher).run(node_rx));
+        if let Some(interval) = options.player_options.client_based_position_update_interval.filter(|ms| *ms > 0) {
+            tokio::spawn(update_positions(players.clone(), Duration::from_millis(interval), shutdown.clone()));
+        }
         
-        (manager, rx)
+        Self {
+            options: options.clone(),
+            node_manager,
+            utils,
+            players,
+            event_sender,
+            shutdown,
+        }
+    }
+
+    fn debug(&self, event: DebugEvents, message: String) {
+        debug!("[{}] {}", event.as_str(), message);
+        let _ = self.event_sender.send(ManagerEvent::Debug { event, message });
+    }
+
+    /// Subscribes to the manager's events. Every subscriber receives every event emitted after it subscribed,
+    /// a subscriber which falls more than 1024 events behind gets `RecvError::Lagged`.
+    pub fn subscribe(&self) -> broadcast::Receiver<ManagerEvent> {
+        self.event_sender.subscribe()
     }
     
-    pub async fn create_player(&self, options: PlayerOptions) -> Result<Player, String> {
+    /// Creates a player on `options.node` if given, otherwise on the least used node serving `options.vc_region`,
+    /// falling back to the least used node overall.
+    pub async fn create_player(&self, options: PlayerOptions) -> Result<Arc<RwLock<Player>>, LavalinkError> {
         let mut players = self.players.write().await;
         
         if players.contains_key(&options.guild_id) {
-            return Err(format!("Player for guild {} already exists", options.guild_id));
+            return Err(LavalinkError::PlayerAlreadyExists { guild_id: options.guild_id });
         }
         
         let guild_id = options.guild_id.clone();
+
+        let node = match &options.node {
+            Some(node_id) => Some(self.node_manager.get_node(node_id).ok_or_else(|| LavalinkError::NodeNotFound { node_id: node_id.clone() })?),
+            None => self.node_manager.least_used_node_in_region(options.vc_region.as_deref()).await,
+        };
         
-        if let Some(node) = self.node_manager.least_used_node() {
-            let player = Player::new(options, node, self.node_manager.event_sender.clone(), false);
+        if let Some(node) = node {
+            let player = Player::new(options, node, self.event_sender.clone(), false)
+                .with_manager_options(self.options.player_options.clone(), self.options.advanced_options.clone());
+            let player = Arc::new(RwLock::new(player));
             players.insert(guild_id, player.clone());
             Ok(player)
         } else {
-            return Err("No available Lavalink node to assign".to_string());
+            Err(LavalinkError::NoAvailableNode)
         }
     }
     
-    pub async fn get_player(&self, guild_id: &str) -> Option<Player> {
+    pub async fn get_player(&self, guild_id: &str) -> Option<Arc<RwLock<Player>>> {
         let players = self.players.read().await;
         players.get(guild_id).cloned() 
     }
@@ -77,26 +184,493 @@ impl LavalinkManager {
         players.remove(guild_id).is_some()
     }
     
-    pub async fn voice_server_update(&self, guild_id: &str, endpoint: &str, session_id: &str, token: &str) -> Result<(), String> {
+    /// Forwards Discord's voice server credentials to the player's node and remembers them for node changes
+    pub async fn voice_server_update(&self, guild_id: &str, endpoint: &str, session_id: &str, token: &str) -> Result<(), LavalinkError> {
+        let voice = crate::types::player::LavalinkPlayerVoiceOptions {
+            endpoint: Some(endpoint.to_string()),
+            session_id: Some(session_id.to_string()),
+            token: Some(token.to_string()),
+        };
         let update_data = crate::types::player::LavalinkPlayOptions {
-            voice: Some(crate::types::player::LavalinkPlayerVoiceOptions {
-                endpoint: Some(endpoint.to_string()),
-                session_id: Some(session_id.to_string()),
-                token: Some(token.to_string()),
-            }),
+            voice: Some(voice.clone()),
             ..Default::default()
         };
 
-        let node = {
-            let mut players = self.players.write().await;
-            if let Some(player) = players.get_mut(guild_id) {
-                player.voice_state.server_deaf = false; 
-                player.node.clone()
-            } else {
-                return Err(format!("Player for guild {} not found during voice server update", guild_id));
-            }
+        let Some(player) = self.get_player(guild_id).await else {
+            return Err(LavalinkError::PlayerNotFound { guild_id: guild_id.to_string() });
         };
+        let mut player = player.write().await;
+        player.voice_state.server_deaf = false;
+        player.voice = Some(voice);
+
+        // Also the first derived region, players are usually created before their region is known
+        if let Some(region) = voice_region_from_endpoint(endpoint)
+            && player.options.vc_region.as_deref() != Some(region.as_str())
+        {
+            player.options.vc_region = Some(region.clone());
+
+            if self.options.move_on_region_change && !player.node.serves_region(&region)
+                && let Some(new_node) = self.node_manager.least_used_node_in_region(Some(&region)).await
+                && new_node.serves_region(&region)
+                && let Err(e) = player.change_node(new_node.clone(), false).await
+            {
+                // The voice credentials still have to reach the player's node
+                self.debug(DebugEvents::PlayerChangeNodeFail, format!("Failed to move player {} to node {} of region {}: {}", guild_id, new_node.id, region, e));
+            }
+        }
+
+        let node = player.node.clone();
+        drop(player);
         
         node.update_player(guild_id, false, &update_data).await.map(|_| ())
     }
 }
+
+impl Drop for LavalinkManager {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+/// Moves the estimated position of every player into `last_position` every `period`, until `shutdown` is cancelled by the dropped manager
+async fn update_positions(players: PlayerMap, period: Duration, shutdown: CancellationToken) {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = interval.tick() => {},
+        }
+        let players: Vec<_> = players.read().await.values().cloned().collect();
+        for player in players {
+            player.write().await.sync_position();
+        }
+    }
+}
+
+/// Background task which consumes the raw node messages, keeps the stored players in sync with them and emits the typed events.
+struct EventDispatcher {
+    options: LavalinkManagerOptions,
+    node_manager: NodeManager,
+    players: PlayerMap,
+    utils: ManagerUtils,
+    event_sender: broadcast::Sender<ManagerEvent>,
+    /// The nodes keep the channel open, so the dispatcher stops once this is cancelled by the dropped manager
+    shutdown: CancellationToken,
+}
+
+impl EventDispatcher {
+    async fn run(self: Arc<Self>, mut node_rx: mpsc::Receiver<NodeMessage>) {
+        loop {
+            let msg = tokio::select! {
+                _ = self.shutdown.cancelled() => break,
+                msg = node_rx.recv() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+            };
+            match msg {
+                NodeMessage::Connected { node_id } => self.emit(ManagerEvent::NodeConnect { node_id }),
+                NodeMessage::Disconnected { node_id, reason } => {
+                    self.emit(ManagerEvent::NodeDisconnect { node_id: node_id.clone(), reason });
+                    if self.options.auto_move {
+                        self.move_players_from(&node_id).await;
+                    }
+                },
+                NodeMessage::Reconnecting { node_id, attempt, delay_ms } => self.emit(ManagerEvent::NodeReconnecting { node_id, attempt, delay_ms }),
+                NodeMessage::ReconnectFailed { node_id, attempts } => {
+                    self.destroy_players_of(&node_id, DestroyReasons::NodeReconnectFail).await;
+                    self.emit(ManagerEvent::NodeReconnectFailed { node_id, attempts });
+                },
+                NodeMessage::Destroyed { node_id, reason } => {
+                    self.destroy_players_of(&node_id, reason).await;
+                    self.emit(ManagerEvent::NodeDestroy { node_id, reason: reason.as_str().to_string() });
+                },
+                NodeMessage::Debug { event, message } => self.debug(event, message),
+                NodeMessage::Payload { node_id, message } => {
+                    self.handle_message(&node_id, &message).await;
+                    self.emit(ManagerEvent::NodeRaw { node_id, message });
+                },
+            }
+        }
+    }
+
+    fn emit(&self, event: ManagerEvent) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.event_sender.send(event);
+    }
+
+    fn debug(&self, event: DebugEvents, message: String) {
+        debug!("[{}] {}", event.as_str(), message);
+        self.emit(ManagerEvent::Debug { event, message });
+    }
+
+    /// Moves every player of the given node to the least used connected node (preferring the player's voice region),
+    /// players which can't be moved are destroyed.
+    async fn move_players_from(&self, node_id: &str) {
+        let players: Vec<_> = self.players.read().await.values().cloned().collect();
+        for player in players {
+            let mut player = player.write().await;
+            if player.node.id != node_id {
+                continue;
+            }
+            let guild_id = player.guild_id.clone();
+
+            let new_node = self.node_manager.least_used_node_in_region(player.options.vc_region.as_deref()).await
+                .filter(|node| node.id != node_id);
+            let reason = match new_node {
+                Some(new_node) => match player.change_node(new_node.clone(), false).await {
+                    Ok(_) => {
+                        self.debug(DebugEvents::PlayerChangeNode, format!("Moved player {} from node {} to node {}", guild_id, node_id, new_node.id));
+                        continue;
+                    },
+                    Err(e) => {
+                        self.debug(DebugEvents::PlayerChangeNodeFail, format!("Failed to move player {} to node {}: {}", guild_id, new_node.id, e));
+                        DestroyReasons::PlayerChangeNodeFail
+                    },
+                },
+                None => {
+                    self.debug(DebugEvents::PlayerChangeNodeFailNoEligibleNode, format!("No eligible node to move player {} from node {}", guild_id, node_id));
+                    DestroyReasons::PlayerChangeNodeFailNoEligibleNode
+                },
+            };
+
+            self.destroy_player(player, reason).await;
+        }
+    }
+
+    /// Destroys every player of the given node
+    async fn destroy_players_of(&self, node_id: &str, reason: DestroyReasons) {
+        let players: Vec<_> = self.players.read().await.values().cloned().collect();
+        for player in players {
+            let player = player.write().await;
+            if player.node.id == node_id {
+                self.destroy_player(player, reason).await;
+            }
+        }
+    }
+
+    async fn destroy_player(&self, mut player: RwLockWriteGuard<'_, Player>, reason: DestroyReasons) {
+        let guild_id = player.guild_id.clone();
+        if let Err(e) = player.destroy(Some(reason.as_str().to_string()), true).await {
+            warn!("Failed to destroy player {}: {}", guild_id, e);
+        }
+        drop(player);
+        self.players.write().await.remove(&guild_id);
+    }
+
+    async fn handle_message(self: &Arc<Self>, node_id: &str, msg: &LavalinkMessage) {
+        if let Some(guild_id) = msg.guild_id()
+            && self.is_from_previous_node(node_id, guild_id).await {
+            return;
+        }
+
+        match msg {
+            LavalinkMessage::Ready { session_id, resumed } => {
+                if let Some(resume) = &self.options.resume
+                    && let Some(node) = self.node_manager.get_node(node_id) {
+                    self.enable_resuming(&node, resume, session_id, *resumed).await;
+                }
+                self.emit(ManagerEvent::NodeReady {
+                    node_id: node_id.to_string(),
+                    session_id: session_id.clone(),
+                    resumed: *resumed,
+                });
+            },
+            LavalinkMessage::Stats(_) => {},
+            LavalinkMessage::PlayerUpdate { guild_id, state } => self.player_update(guild_id, state).await,
+            LavalinkMessage::Event(event) => match event {
+                PlayerEvent::TrackStartEvent { guild_id, track } => self.track_start(guild_id, track).await,
+                PlayerEvent::TrackEndEvent { guild_id, track, reason } => self.track_end(guild_id, track, reason).await,
+                PlayerEvent::TrackStuckEvent { guild_id, threshold_ms, track } => {
+                    let track = self.event_track(guild_id, track).await;
+                    self.emit(ManagerEvent::TrackStuck { guild_id: guild_id.clone(), track, threshold_ms: *threshold_ms });
+                },
+                PlayerEvent::TrackExceptionEvent { guild_id, exception, track, .. } => self.track_error(guild_id, track, exception).await,
+                PlayerEvent::WebSocketClosedEvent { guild_id, code, by_remote, reason } => self.emit(ManagerEvent::PlayerSocketClosed {
+                    guild_id: guild_id.clone(),
+                    code: *code,
+                    reason: reason.clone(),
+                    by_remote: *by_remote,
+                }),
+                PlayerEvent::SegmentsLoaded { guild_id, segments } => self.emit(ManagerEvent::SegmentsLoaded { guild_id: guild_id.clone(), segments: segments.clone() }),
+                PlayerEvent::SegmentSkipped { guild_id, segment } => self.emit(ManagerEvent::SegmentSkipped { guild_id: guild_id.clone(), segment: segment.clone() }),
+                PlayerEvent::ChaptersLoaded { guild_id, chapters } => self.emit(ManagerEvent::ChaptersLoaded { guild_id: guild_id.clone(), chapters: chapters.clone() }),
+                PlayerEvent::ChapterStarted { guild_id, chapter } => self.emit(ManagerEvent::ChapterStarted { guild_id: guild_id.clone(), chapter: chapter.clone() }),
+            },
+        }
+    }
+
+    /// Keeps the new session resumable and persists it, rebuilds the node's players when the session was resumed
+    async fn enable_resuming(&self, node: &Arc<LavalinkNode>, resume: &ResumeOptions, session_id: &str, resumed: bool) {
+        if let Err(e) = node.update_session(Some(true), Some(resume.timeout)).await {
+            warn!("Failed to enable resuming on node {}: {}", node.id, e);
+        }
+        resume.store.set(&node.id, session_id).await;
+
+        if !resumed {
+            return;
+        }
+        match node.fetch_all_players().await {
+            Ok(states) => {
+                for state in states {
+                    self.restore_player(node, state).await;
+                }
+            },
+            Err(e) => self.debug(DebugEvents::ResumingFetchingError, format!("Failed to fetch the players of resumed node {}: {}", node.id, e)),
+        }
+    }
+
+    /// Applies a player's state on the node to the stored player, creating it when it is unknown (e.g. after a restart)
+    async fn restore_player(&self, node: &Arc<LavalinkNode>, state: LavalinkPlayer) {
+        let player = {
+            let mut players = self.players.write().await;
+            players.entry(state.guild_id.clone()).or_insert_with(|| {
+                let options = PlayerOptions {
+                    guild_id: state.guild_id.clone(),
+                    // Lavalink doesn't know the voice channel, it has to be set again by the user
+                    voice_channel_id: String::new(),
+                    text_channel_id: None,
+                    volume: None,
+                    vc_region: state.voice.endpoint.as_deref().and_then(voice_region_from_endpoint),
+                    self_deaf: None,
+                    self_mute: None,
+                    node: Some(node.id.clone()),
+                    insta_update_filters_fix: None,
+                    apply_volume_as_filter: None,
+                    custom_data: None,
+                };
+                let mut player = Player::new(options, node.clone(), self.event_sender.clone(), false)
+                    .with_manager_options(self.options.player_options.clone(), self.options.advanced_options.clone());
+                player.voice_channel_id = None;
+                Arc::new(RwLock::new(player))
+            }).clone()
+        };
+        let mut player = player.write().await;
+        if player.node.id != node.id {
+            return;
+        }
+
+        let current = state.track.as_ref().and_then(|track| self.to_track(player.queue.current.as_ref(), track));
+        player.playing = current.is_some() && !state.paused;
+        player.queue.current = current;
+        player.paused = state.paused;
+        player.last_position = state.state.position;
+        player.last_position_change = if state.paused { None } else { Some(chrono::Utc::now().timestamp_millis()) };
+        player.connected = state.state.connected;
+        if let Some(ping) = state.state.ping.filter(|p| *p >= 0) {
+            player.ping.ws = ping;
+        }
+        if let (Some(endpoint), Some(session_id), Some(token)) = (state.voice.endpoint, state.voice.session_id, state.voice.token) {
+            player.voice = Some(LavalinkPlayerVoiceOptions { endpoint: Some(endpoint), session_id: Some(session_id), token: Some(token) });
+        }
+        player.filter_manager.set_data(state.filters);
+        player.filter_manager.filter_updated_state = false;
+        player.restore_volume(state.volume);
+        player.last_position_speed = player.filter_manager.playback_speed();
+    }
+
+    /// Whether the guild's player was moved away from the node which sent the message, e.g. the old node's cleanup after a node change
+    async fn is_from_previous_node(&self, node_id: &str, guild_id: &str) -> bool {
+        let Some(player) = self.players.read().await.get(guild_id).cloned() else { return false };
+        let current_node = player.read().await.node.id.clone();
+        if current_node == node_id {
+            return false;
+        }
+        self.debug(DebugEvents::PlayerChangeNode, format!("Ignoring a message of node {} for guild {}, its player is on node {}", node_id, guild_id, current_node));
+        true
+    }
+
+    async fn get_player(&self, guild_id: &str) -> Option<Arc<RwLock<Player>>> {
+        let player = self.players.read().await.get(guild_id).cloned();
+        if player.is_none() {
+            self.debug(DebugEvents::PlayerUpdateNoPlayer, format!("Received an update for guild {} without a stored player", guild_id));
+        }
+        player
+    }
+
+    /// Returns the player's current track if it is the given one (to keep its requester), otherwise builds it.
+    fn to_track(&self, current: Option<&Track>, track: &LavalinkTrack) -> Option<Track> {
+        if let Some(current) = current.filter(|c| c.encoded.is_some() && c.encoded == track.encoded) {
+            return Some(current.clone());
+        }
+        match self.utils.build_track(track.clone(), None) {
+            Ok(built) => Some(built),
+            Err(e) => {
+                self.debug(DebugEvents::BuildTrackError, format!("Failed to build track \"{}\": {}", track.info.title, e));
+                None
+            },
+        }
+    }
+
+    async fn event_track(&self, guild_id: &str, track: &LavalinkTrack) -> Option<Track> {
+        let player = self.players.read().await.get(guild_id).cloned();
+        match player {
+            Some(player) => self.to_track(player.read().await.queue.current.as_ref(), track),
+            None => self.to_track(None, track),
+        }
+    }
+
+    async fn player_update(&self, guild_id: &str, state: &LavalinkPlayerState) {
+        let Some(player) = self.get_player(guild_id).await else { return };
+        let mut player = player.write().await;
+
+        player.last_position = state.position;
+        player.last_position_change = if player.paused { None } else { Some(chrono::Utc::now().timestamp_millis()) };
+        player.connected = state.connected;
+        if let Some(ping) = state.ping.filter(|p| *p >= 0) {
+            player.ping.ws = ping;
+        }
+    }
+
+    async fn track_start(&self, guild_id: &str, track: &LavalinkTrack) {
+        let Some(player) = self.get_player(guild_id).await else { return };
+        let mut player = player.write().await;
+
+        // Tracks started with `paused: true` start paused, so `paused` is kept as `Player::play` set it
+        player.playing = true;
+        player.last_position = 0;
+        player.last_position_change = if player.paused { None } else { Some(chrono::Utc::now().timestamp_millis()) };
+
+        let Some(track) = self.to_track(player.queue.current.as_ref(), track) else {
+            self.debug(DebugEvents::TrackStartNoTrack, format!("Started track of guild {} could not be built", guild_id));
+            return;
+        };
+        player.queue.current = Some(track.clone());
+
+        if self.options.emit_new_songs_only && player.queue.previous.first().is_some_and(|p| p.encoded == track.encoded) {
+            self.debug(DebugEvents::TrackStartNewSongsOnly, format!("Not emitting TrackStart of the repeated track in guild {}", guild_id));
+            return;
+        }
+        self.emit(ManagerEvent::TrackStart { guild_id: guild_id.to_string(), track });
+    }
+
+    async fn track_error(&self, guild_id: &str, track: &LavalinkTrack, exception: &Option<Exception>) {
+        let track = self.event_track(guild_id, track).await;
+        self.emit(ManagerEvent::TrackError { guild_id: guild_id.to_string(), track, exception: exception.clone() });
+    }
+
+    async fn track_end(self: &Arc<Self>, guild_id: &str, track: &LavalinkTrack, reason: &str) {
+        // A replaced track is followed by a TrackStartEvent of the new track
+        if reason == "replaced" {
+            let track = self.event_track(guild_id, track).await;
+            self.emit(ManagerEvent::TrackEnd { guild_id: guild_id.to_string(), track, reason: reason.to_string() });
+            return;
+        }
+
+        let Some(player) = self.get_player(guild_id).await else { return };
+        // The lock is taken in the order of the events, but advancing the queue may resolve and play tracks on the node,
+        // so it runs on its own task instead of holding up the events of every other guild
+        let player = player.write_owned().await;
+        let dispatcher = self.clone();
+        let (guild_id, track, reason) = (guild_id.to_string(), track.clone(), reason.to_string());
+        tokio::spawn(async move { dispatcher.advance_queue(player, &guild_id, &track, &reason).await });
+    }
+
+    async fn advance_queue(&self, mut player: OwnedRwLockWriteGuard<Player>, guild_id: &str, track: &LavalinkTrack, reason: &str) {
+        player.playing = false;
+        player.last_position_change = None;
+        let ended_track = self.to_track(player.queue.current.as_ref(), track);
+        let track_end = ManagerEvent::TrackEnd { guild_id: guild_id.to_string(), track: ended_track, reason: reason.to_string() };
+
+        if player.get("internal_destroystatus").is_some() {
+            self.emit(track_end);
+            return;
+        }
+
+        let skipped = player.data.remove("internal_skipped").is_some();
+        let stop_playing = player.data.remove("internal_stopPlaying").is_some();
+        // Only finished / failed tracks advance the queue, "stopped" only does when it was caused by Player::skip
+        let advance = match reason {
+            "finished" | "loadFailed" => !stop_playing,
+            "stopped" => skipped,
+            _ => false,
+        };
+
+        if !advance {
+            if let Some(current) = player.queue.current.take() {
+                player.queue.previous.insert(0, current);
+            }
+            player.queue.save().await;
+            self.emit(track_end);
+            return;
+        }
+
+        let repeat_track = player.repeat_mode == RepeatMode::Track && reason == "finished" && !skipped;
+        let ended = if repeat_track {
+            // Keep the current track and replay it
+            let current = player.queue.current.clone();
+            if let Some(current) = &current {
+                player.queue.previous.insert(0, current.clone());
+            }
+            player.queue.save().await;
+            current
+        } else {
+            self.queue_track_end(&mut player).await
+        };
+        self.emit(track_end);
+
+        if player.queue.current.is_none() {
+            self.debug(DebugEvents::QueueEnded, format!("Queue of guild {} ended", guild_id));
+            player.set("internal_queueempty", Value::Bool(true));
+            self.emit(ManagerEvent::QueueEnd { guild_id: guild_id.to_string(), track: ended });
+            return;
+        }
+
+        if self.options.auto_skip
+            && let Err(e) = player.play(PlayOptions { no_replace: Some(true), ..Default::default() }).await {
+            warn!("Failed to play the next track for guild {}: {}", guild_id, e);
+        }
+    }
+
+    /// Moves the current track into the previous tracks (and to the end of the queue for RepeatMode::Queue) and shifts the next one.
+    /// Returns the track which ended.
+    async fn queue_track_end(&self, player: &mut Player) -> Option<Track> {
+        let ended = player.queue.current.take();
+        if let Some(current) = &ended {
+            player.queue.previous.insert(0, current.clone());
+            if player.repeat_mode == RepeatMode::Queue {
+                player.queue.tracks.push(QueueTrack::Resolved(current.clone()));
+            }
+        }
+
+        while !player.queue.tracks.is_empty() {
+            let next = player.queue.tracks.remove(0);
+            match self.resolve_queue_track(player, next).await {
+                Ok(track) => {
+                    player.queue.current = Some(track);
+                    break;
+                },
+                Err(e) => self.debug(DebugEvents::PlayerPlayUnresolvedTrackFailed, format!("Failed to resolve the next track of guild {}: {}", player.guild_id, e)),
+            }
+        }
+
+        player.queue.save().await;
+        ended
+    }
+
+    async fn resolve_queue_track(&self, player: &Player, track: QueueTrack) -> Result<Track, LavalinkError> {
+        let unresolved = match track {
+            QueueTrack::Resolved(track) => return Ok(track),
+            QueueTrack::Unresolved(unresolved) => unresolved,
+        };
+        let requester_id = unresolved.requester.as_ref().and_then(|r| r.id.clone());
+
+        let lavalink_track = if let Some(encoded) = &unresolved.encoded {
+            player.node.decode_single_track(encoded).await?
+        } else {
+            let query = match &unresolved.info.author {
+                Some(author) => format!("{}:{} {}", self.utils.default_search_platform, unresolved.info.title, author),
+                None => format!("{}:{}", self.utils.default_search_platform, unresolved.info.title),
+            };
+            player.node.search(&query).await?.tracks().into_iter().next()
+                .ok_or_else(|| LavalinkError::InvalidQuery(format!("No track found for \"{}\"", unresolved.info.title)))?
+        };
+
+        let mut track = self.utils.build_track(lavalink_track, requester_id)?;
+        if unresolved.user_data.is_some() {
+            track.user_data = unresolved.user_data;
+        }
+        Ok(track)
+    }
+}
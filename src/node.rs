@@ -1,17 +1,30 @@
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::sync::RwLock;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use futures_util::StreamExt;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::{CloseFrame, Message, frame::coding::CloseCode}};
+use futures_util::{SinkExt, StreamExt};
 use reqwest::header::HeaderValue;
+use reqwest::{Method, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 use tracing::{info, warn, error, debug};
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 
-use crate::types::events::{LavalinkMessage, SearchResult, LavalinkPlayer};
-use crate::types::player::LavalinkPlayOptions;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::types::events::{LavalinkMessage, NodeMessage, SearchResult, LavalinkPlayer};
+use crate::types::node::{LavalinkInfo, LavalinkNodeAutoChecks, NodeCapability, NodeStatsSnapshot, NodeType, ReconnectionState};
+use std::collections::VecDeque;
+use crate::constants::{LavalinkPlugins, RecommendationsStrings};
+use crate::types::node_link::{HealthStatusObject, HealthStatusThreshold, HealthStatusThresholdOptions, NodeMetricSummary};
+use crate::types::utils::{InvalidLavalinkRestRequest, LavaSearchResponse};
+use crate::types::player::{LavalinkPlayOptions, SponsorBlockSegment};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use crate::constants::{DebugEvents, DestroyReasons};
+use crate::error::LavalinkError;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkNodeOptions {
     pub id: String,
@@ -19,62 +32,303 @@ pub struct LavalinkNodeOptions {
     pub port: u16,
     pub authorization: String,
     pub secure: Option<bool>,
+    /// Timeout in ms for each REST request, default no timeout
     pub request_timeout: Option<u64>,
     pub session_id: Option<String>,
+    /// Discord voice regions this node should serve, e.g. `["us-east", "us-central"]`
+    #[serde(default)]
+    pub regions: Option<Vec<String>>,
+    /// How often to try reconnecting before giving up, default 5
+    #[serde(default)]
+    pub retry_amount: Option<u32>,
+    /// Base delay in ms between reconnect attempts, doubled per attempt, default 5000
+    #[serde(default)]
+    pub retry_delay: Option<u64>,
+    /// Give up reconnecting once this many ms passed since the connection was lost, default no limit
+    #[serde(default)]
+    pub retry_timespan: Option<u64>,
+    /// How often idempotent REST requests (searches, decoding, info, stats, ...) are retried
+    /// after a timeout, a connection error or a 5xx response, default 0
+    #[serde(default)]
+    pub request_retries: Option<u32>,
+    /// Delay in ms between REST retries, default 500
+    #[serde(default)]
+    pub request_retry_delay: Option<u64>,
+    /// Interval in ms between WebSocket pings, the node reconnects when a ping is not answered
+    /// before the next one is due. Default 30000, `Some(0)` disables the heartbeat
+    #[serde(default)]
+    pub heart_beat_interval: Option<u64>,
+    /// The client used for REST requests, e.g. one with a proxy or shared between nodes.
+    /// A new client is created when `None`
+    #[serde(skip)]
+    pub http_client: Option<reqwest::Client>,
+    /// Validations of searches against the node's info, all enabled when `None`
+    #[serde(default)]
+    pub auto_checks: Option<LavalinkNodeAutoChecks>,
+    /// The node's server, detected from its info on connect when `None`
+    #[serde(default)]
+    pub node_type: Option<NodeType>,
+    /// How many stats ops `stats_history` keeps, default 60 (an hour with Lavalink's interval), 0 disables it
+    #[serde(default)]
+    pub stats_history_size: Option<usize>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct NodeStats {
-    pub players: i32,
-    pub playing_players: i32,
-    pub uptime: i64,
-    pub memory: NodeMemoryStats,
-    pub cpu: NodeCpuStats,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct NodeMemoryStats {
-    pub free: u64,
-    pub used: u64,
-    pub allocated: u64,
-    pub reservable: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct NodeCpuStats {
-    pub cores: i32,
-    pub system_load: f32,
-    pub lavalink_load: f32,
-}
+/// Reconnect attempts when `retry_amount` is not set
+pub const DEFAULT_RETRY_AMOUNT: u32 = 5;
+/// Reconnect base delay when `retry_delay` is not set
+pub const DEFAULT_RETRY_DELAY_MS: u64 = 5_000;
+/// Upper bound for the exponential reconnect delay
+pub const MAX_RETRY_DELAY_MS: u64 = 60_000;
+/// Kept stats ops when `stats_history_size` is not set
+pub const DEFAULT_STATS_HISTORY_SIZE: usize = 60;
+/// Heartbeat interval when `heart_beat_interval` is not set
+pub const DEFAULT_HEART_BEAT_INTERVAL_MS: u64 = 30_000;
+/// Delay between REST retries when `request_retry_delay` is not set
+pub const DEFAULT_REQUEST_RETRY_DELAY_MS: u64 = 500;
+
+// The stats types live in `types::node`, re-exported under their previous names
+pub use crate::types::node::{CPUStats as NodeCpuStats, MemoryStats as NodeMemoryStats, NodeStats};
 
 pub struct LavalinkNode {
     pub id: String,
     pub options: LavalinkNodeOptions,
     pub session_id: RwLock<Option<String>>,
     pub stats: RwLock<Option<NodeStats>>,
+    // The latest stats ops, oldest first
+    stats_history: RwLock<VecDeque<NodeStatsSnapshot>>,
+    /// The node's `/v4/info`, fetched on every connect and again on demand when that failed
+    pub info: RwLock<Option<LavalinkInfo>>,
     pub connected: RwLock<bool>,
+    pub reconnection_state: RwLock<ReconnectionState>,
+    /// Round trip time in ms of the last answered WebSocket ping
+    pub ping: RwLock<Option<i64>>,
+    // Sends messages through the open WebSocket
+    outgoing: Mutex<Option<mpsc::UnboundedSender<Message>>>,
+    // The WebSocket task and the token stopping it
+    task: Mutex<Option<(CancellationToken, JoinHandle<()>)>>,
     reqwest_client: reqwest::Client,
     // Channel to push events to the NodeManager
-    pub event_sender: mpsc::Sender<LavalinkMessage>,
+    pub event_sender: mpsc::Sender<NodeMessage>,
 }
 
 impl LavalinkNode {
-    pub fn new(options: LavalinkNodeOptions, event_sender: mpsc::Sender<LavalinkMessage>) -> Self {
+    pub fn new(options: LavalinkNodeOptions, event_sender: mpsc::Sender<NodeMessage>) -> Self {
         let initial_session = options.session_id.clone();
+        let reqwest_client = options.http_client.clone().unwrap_or_default();
         Self {
             id: options.id.clone(),
             options,
             session_id: RwLock::new(initial_session),
             stats: RwLock::new(None),
+            stats_history: RwLock::new(VecDeque::new()),
+            info: RwLock::new(None),
             connected: RwLock::new(false),
-            reqwest_client: reqwest::Client::new(),
+            reconnection_state: RwLock::new(ReconnectionState::Idle),
+            ping: RwLock::new(None),
+            outgoing: Mutex::new(None),
+            task: Mutex::new(None),
+            reqwest_client,
             event_sender,
         }
     }
 
+    /// Whether the node lists the given voice region in its `regions`, compared case-insensitively
+    pub fn serves_region(&self, region: &str) -> bool {
+        self.options.regions.as_ref().is_some_and(|regions| regions.iter().any(|r| r.eq_ignore_ascii_case(region)))
+    }
+
+    /// Sends a JSON message through the node's WebSocket, e.g. NodeLink specific ops.
+    /// Fails when the WebSocket is not open.
+    pub fn send<T: Serialize>(&self, payload: &T) -> Result<(), LavalinkError> {
+        let text = serde_json::to_string(payload)?;
+        let outgoing = self.outgoing.lock().unwrap_or_else(PoisonError::into_inner);
+        outgoing.as_ref()
+            .and_then(|sender| sender.send(Message::Text(text.into())).ok())
+            .ok_or_else(|| LavalinkError::InvalidState(format!("The WebSocket of node {} is not open", self.id)))
+    }
+
+    /// The node's server: `options.node_type`, else detected from the cached info.
+    /// Nodes whose info is not loaded are treated as Lavalink, use [`LavalinkNode::load_info`] first when it matters.
+    pub async fn node_type(&self) -> NodeType {
+        if let Some(node_type) = self.options.node_type {
+            return node_type;
+        }
+        match self.info.read().await.as_ref() {
+            Some(info) if info.is_nodelink == Some(true) => NodeType::NodeLink,
+            _ => NodeType::Lavalink,
+        }
+    }
+
+    /// The server's semver from the cached info
+    pub async fn version(&self) -> Option<String> {
+        self.info.read().await.as_ref().map(|info| info.version.semver.clone())
+    }
+
+    /// Fetches the info unless it is cached, e.g. when the fetch on connect failed
+    pub async fn load_info(&self) -> Result<(), LavalinkError> {
+        if self.info.read().await.is_none() {
+            self.fetch_info().await?;
+        }
+        Ok(())
+    }
+
+    /// Whether the node supports the capability, plugin based ones are checked against the cached info
+    pub async fn supports(&self, capability: NodeCapability) -> bool {
+        let plugin = match capability {
+            NodeCapability::LavaSearch => LavalinkPlugins::LAVA_SEARCH,
+            NodeCapability::LavaLyrics => LavalinkPlugins::LAVA_LYRICS,
+            NodeCapability::SponsorBlock => LavalinkPlugins::SPONSOR_BLOCK,
+            _ => return self.node_type().await == NodeType::NodeLink,
+        };
+        self.info.read().await.as_ref()
+            .is_some_and(|info| info.plugins.iter().any(|p| p.name.eq_ignore_ascii_case(plugin)))
+    }
+
+    /// The capabilities of [`NodeCapability`] the node supports
+    pub async fn capabilities(&self) -> Vec<NodeCapability> {
+        let mut capabilities = Vec::new();
+        for capability in [
+            NodeCapability::Mixer,
+            NodeCapability::NodeLinkFilters,
+            NodeCapability::NodeLinkLyrics,
+            NodeCapability::ConnectionMetrics,
+            NodeCapability::DirectStream,
+            NodeCapability::AudioTrackLanguage,
+            NodeCapability::YoutubeConfig,
+            NodeCapability::LavaSearch,
+            NodeCapability::LavaLyrics,
+            NodeCapability::SponsorBlock,
+        ] {
+            if self.supports(capability).await {
+                capabilities.push(capability);
+            }
+        }
+        capabilities
+    }
+
+    /// Fails with [`LavalinkError::Unsupported`] when the node doesn't support the capability,
+    /// loads the info first so a failed fetch on connect doesn't disable every capability
+    pub(crate) async fn require(&self, capability: NodeCapability) -> Result<(), LavalinkError> {
+        self.load_info().await?;
+        if self.supports(capability).await {
+            Ok(())
+        } else {
+            Err(LavalinkError::Unsupported { node_id: self.id.clone(), capability })
+        }
+    }
+
+    /// The latest stats ops with the time they were received, oldest first
+    pub async fn stats_history(&self) -> Vec<NodeStatsSnapshot> {
+        self.stats_history.read().await.iter().cloned().collect()
+    }
+
+    async fn record_stats(&self, stats: &NodeStats) {
+        let size = self.options.stats_history_size.unwrap_or(DEFAULT_STATS_HISTORY_SIZE);
+        let mut history = self.stats_history.write().await;
+        history.push_back(NodeStatsSnapshot { received_at: chrono::Utc::now().timestamp_millis(), stats: stats.clone() });
+        while history.len() > size {
+            history.pop_front();
+        }
+    }
+
+    /// The node's health, derived from its latest stats, frame stats and ping.
+    /// Thresholds which are not set use [`HealthStatusThreshold::CPU`], [`HealthStatusThreshold::MEMORY`] and [`HealthStatusThreshold::PING`].
+    pub async fn health_status(&self, thresholds: &HealthStatusThresholdOptions) -> HealthStatusObject {
+        let cpu_thresholds = thresholds.cpu.clone().unwrap_or(HealthStatusThreshold::CPU);
+        let memory_thresholds = thresholds.memory.clone().unwrap_or(HealthStatusThreshold::MEMORY);
+        let ping_thresholds = thresholds.ping.clone().unwrap_or(HealthStatusThreshold::PING);
+
+        let stats = self.stats.read().await.clone();
+        let ping = *self.ping.read().await;
+        let (Some(stats), true) = (stats, *self.connected.read().await) else {
+            return HealthStatusObject {
+                status: "offline".to_string(),
+                performance: "poor".to_string(),
+                is_overloaded: false,
+                needs_restart: true,
+                penalty_score: f64::MAX,
+                estimated_remaining_capacity: 0.0,
+                recommendations: vec![RecommendationsStrings::node_offline().to_string(), RecommendationsStrings::check_connectivity().to_string()],
+                metrics: NodeMetricSummary {
+                    cpu_load: 0.0,
+                    system_load: 0.0,
+                    memory_usage: 0.0,
+                    players: 0,
+                    playing_players: 0,
+                    uptime: 0,
+                    ping: ping.unwrap_or(0),
+                    frame_deficit: 0,
+                },
+            };
+        };
+
+        let cpu_load = stats.cpu.lavalink_load;
+        let system_load = stats.cpu.system_load;
+        let memory_limit = if stats.memory.reservable > 0 { stats.memory.reservable } else { stats.memory.allocated };
+        let memory_usage = if memory_limit > 0 { stats.memory.used as f64 / memory_limit as f64 } else { 0.0 };
+        let frame_deficit = stats.frame_deficit();
+
+        let mut recommendations = Vec::new();
+        let mut levels = vec![cpu_thresholds.performance(cpu_load.max(system_load)), memory_thresholds.performance(memory_usage)];
+        if cpu_load > cpu_thresholds.fair {
+            recommendations.push(RecommendationsStrings::high_cpu_load(cpu_load));
+        }
+        if system_load > cpu_thresholds.fair {
+            recommendations.push(RecommendationsStrings::high_system_load(system_load));
+        }
+        if memory_usage > memory_thresholds.fair {
+            recommendations.push(RecommendationsStrings::high_memory_usage(memory_usage * 100.0));
+        }
+        if frame_deficit > 0 {
+            recommendations.push(RecommendationsStrings::frame_deficit(frame_deficit));
+        }
+        if let Some(ping) = ping {
+            levels.push(ping_thresholds.performance(ping as f64));
+            if ping as f64 > ping_thresholds.fair {
+                recommendations.push(RecommendationsStrings::high_latency(ping));
+            }
+        }
+
+        let is_overloaded = cpu_load.max(system_load) > cpu_thresholds.poor || memory_usage > memory_thresholds.poor;
+        let needs_restart = memory_usage > memory_thresholds.poor;
+        if needs_restart {
+            recommendations.push(RecommendationsStrings::node_restart().to_string());
+        }
+        let performance = ["poor", "fair", "good", "excellent"].into_iter().find(|level| levels.contains(level)).unwrap_or("excellent");
+        let status = if is_overloaded {
+            "unhealthy"
+        } else if performance == "poor" || performance == "fair" || frame_deficit > 0 {
+            "degraded"
+        } else {
+            "healthy"
+        };
+
+        HealthStatusObject {
+            status: status.to_string(),
+            performance: performance.to_string(),
+            is_overloaded,
+            needs_restart,
+            penalty_score: stats.penalties(),
+            estimated_remaining_capacity: ((1.0 - cpu_load.max(memory_usage)) * 100.0).max(0.0),
+            recommendations,
+            metrics: NodeMetricSummary {
+                cpu_load,
+                system_load,
+                memory_usage: memory_usage * 100.0,
+                players: stats.players,
+                playing_players: stats.playing_players,
+                uptime: stats.uptime,
+                ping: ping.unwrap_or(0),
+                frame_deficit,
+            },
+        }
+    }
+
+    /// The current session id, fails when the node is not ready yet
+    pub(crate) async fn require_session(&self) -> Result<String, LavalinkError> {
+        self.session_id.read().await.clone().ok_or_else(|| LavalinkError::NodeNotReady { node_id: self.id.clone() })
+    }
+
     pub fn get_rest_url(&self) -> String {
         let protocol = if self.options.secure.unwrap_or(false) { "https" } else { "http" };
         format!("{}://{}:{}", protocol, self.options.host, self.options.port)
@@ -85,289 +339,501 @@ impl LavalinkNode {
         format!("{}://{}:{}", protocol, self.options.host, self.options.port)
     }
 
-    pub async fn connect(node_arc: Arc<Self>, user_id: String, client_name: String) -> Result<(), String> {
+    /// Connects to the node's WebSocket in a background task, which reconnects with exponential backoff
+    /// until `retry_amount` attempts or `retry_timespan` are exhausted. Fails when the task is already running.
+    pub async fn connect(node_arc: Arc<Self>, user_id: String, client_name: String) -> Result<(), LavalinkError> {
+        let mut task = node_arc.task.lock().unwrap_or_else(PoisonError::into_inner);
+        if task.as_ref().is_some_and(|(_, handle)| !handle.is_finished()) {
+            return Err(LavalinkError::InvalidState(format!("Node {} is already connecting or connected", node_arc.id)));
+        }
+
+        let cancel = CancellationToken::new();
         let n = node_arc.clone();
-        
-        tokio::spawn(async move {
+        let token = cancel.clone();
+        let handle = tokio::spawn(async move {
+            let max_attempts = n.options.retry_amount.unwrap_or(DEFAULT_RETRY_AMOUNT);
+            let mut attempts: u32 = 0;
+            // When the node was lost, or the first connection attempt failed
+            let mut lost_at: Option<Instant> = None;
+
             loop {
-                let ws_url = format!("{}/v4/websocket", n.get_ws_url());
-                let request = ws_url.into_client_request();
-                
-                if let Ok(mut request) = request {
-                    let headers = request.headers_mut();
-                    headers.insert("Authorization", HeaderValue::from_str(&n.options.authorization).unwrap());
-                    headers.insert("User-Id", HeaderValue::from_str(&user_id).unwrap());
-                    headers.insert("Client-Name", HeaderValue::from_str(&client_name).unwrap());
-                    
-                    if let Some(session_id) = n.session_id.read().await.clone() {
-                        headers.insert("Session-Id", HeaderValue::from_str(&session_id).unwrap());
-                    }
+                *n.reconnection_state.write().await = ReconnectionState::Pending;
+                match n.run_socket(&user_id, &client_name, &token).await {
+                    Ok(close_reason) => {
+                        attempts = 0;
+                        lost_at = Some(Instant::now());
+                        let _ = n.event_sender.send(NodeMessage::Disconnected { node_id: n.id.clone(), reason: close_reason }).await;
+                    },
+                    Err(e) => {
+                        error!("Failed to connect to Websocket ({}): {}", n.id, e);
+                        lost_at.get_or_insert_with(Instant::now);
+                    },
+                }
+                if token.is_cancelled() {
+                    break;
+                }
 
-                    match connect_async(request).await {
-                        Ok((ws_stream, _)) => {
-                            info!("Connected to Lavalink Node {}", n.id);
-                            *n.connected.write().await = true;
-
-                            let (mut _write, mut read) = ws_stream.split();
-                            
-                            while let Some(msg) = read.next().await {
-                                match msg {
-                                    Ok(Message::Text(text)) => {
-                                        debug!("Received WebSocket message: {}", text);
-                                        match serde_json::from_str::<LavalinkMessage>(&text) {
-                                            Ok(lavalink_msg) => {
-                                                match &lavalink_msg {
-                                                    LavalinkMessage::Ready { session_id, .. } => {
-                                                        *n.session_id.write().await = Some(session_id.clone());
-                                                        info!("Lavalink Node {} is READY with session {}", n.id, session_id);
-                                                    },
-                                                    LavalinkMessage::Stats(stats) => {
-                                                        *n.stats.write().await = Some(stats.clone());
-                                                    },
-                                                    _ => {}
-                                                }
-                                                let _ = n.event_sender.send(lavalink_msg).await;
-                                            },
-                                            Err(e) => warn!("Failed to deserialize Lavalink message: {} | Data: {}", e, text),
-                                        }
-                                    },
-                                    Ok(Message::Close(c)) => {
-                                        warn!("WebSocket closed by node {}: {:?}", n.id, c);
-                                        break;
-                                    },
-                                    Err(e) => {
-                                        warn!("WebSocket error on node {}: {}", n.id, e);
-                                        break;
-                                    },
-                                    _ => {}
-                                }
-                            }
-                            *n.connected.write().await = false;
-                            warn!("Event loop terminated for node {}. Reconnecting in 5s...", n.id);
-                        },
-                        Err(e) => {
-                            error!("Failed to connect to Websocket ({}): {}. Retrying in 5s...", n.id, e);
-                        }
-                    }
+                attempts += 1;
+                let timed_out = n.options.retry_timespan
+                    .zip(lost_at)
+                    .is_some_and(|(timespan, lost_at)| lost_at.elapsed() >= Duration::from_millis(timespan));
+                if attempts > max_attempts || timed_out {
+                    error!("Giving up reconnecting to node {} after {} attempts", n.id, attempts - 1);
+                    *n.reconnection_state.write().await = ReconnectionState::Idle;
+                    let _ = n.event_sender.send(NodeMessage::ReconnectFailed { node_id: n.id.clone(), attempts: attempts - 1 }).await;
+                    return;
+                }
+
+                let delay = n.reconnect_delay(attempts);
+                warn!("Reconnecting to node {} in {}ms (attempt {}/{})", n.id, delay.as_millis(), attempts, max_attempts);
+                *n.reconnection_state.write().await = ReconnectionState::Reconnecting;
+                let _ = n.event_sender.send(NodeMessage::Reconnecting { node_id: n.id.clone(), attempt: attempts, delay_ms: delay.as_millis() as u64 }).await;
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {},
+                    _ = token.cancelled() => break,
                 }
-                
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
             }
+            *n.reconnection_state.write().await = ReconnectionState::Idle;
         });
+        *task = Some((cancel, handle));
 
         Ok(())
     }
 
-    /// Update the Player on the Lavalink Server
-    pub async fn update_player(&self, guild_id: &str, no_replace: bool, update_data: &LavalinkPlayOptions) -> Result<LavalinkPlayer, String> {
-        let session = self.session_id.read().await.clone();
-        if session.is_none() {
-            return Err(format!("Node {} is not ready yet: No Session ID", self.id));
-        }
-        
-        let url = format!("{}/v4/sessions/{}/players/{}?noReplace={}", self.get_rest_url(), session.unwrap(), guild_id, no_replace);
-        
-        let req = self.reqwest_client
-            .patch(&url)
-            .header("Authorization", &self.options.authorization)
-            .json(update_data)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
-            
-        if !req.status().is_success() {
-            let status = req.status();
-            let body = req.text().await.unwrap_or_default();
-            return Err(format!("Failed to update player ({}): {}", status, body));
+    /// Closes the WebSocket with a close frame and stops reconnecting, `connect` can be called again afterwards.
+    /// Players stay on the node, with `auto_move` the manager moves them to other nodes.
+    pub async fn disconnect(&self) {
+        let task = self.task.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some((cancel, handle)) = task {
+            cancel.cancel();
+            let _ = handle.await;
         }
+    }
 
-        let player = req.json::<LavalinkPlayer>().await.map_err(|e| e.to_string())?;
-        Ok(player)
+    /// Disconnects the node and destroys (or with `auto_move` moves) its players with `DestroyReasons::NodeDestroy`
+    pub async fn destroy(&self) {
+        self.shutdown(DestroyReasons::NodeDestroy).await;
     }
 
-    /// Destroy the Player on the Lavalink Server
-    pub async fn destroy_player(&self, guild_id: &str) -> Result<(), String> {
-        let session = self.session_id.read().await.clone();
-        if session.is_none() {
-            return Err(format!("Node {} is not ready yet: No Session ID", self.id));
+    pub(crate) async fn shutdown(&self, reason: DestroyReasons) {
+        *self.reconnection_state.write().await = ReconnectionState::Destroying;
+        self.disconnect().await;
+        *self.session_id.write().await = None;
+        let _ = self.event_sender.send(NodeMessage::Destroyed { node_id: self.id.clone(), reason }).await;
+        *self.reconnection_state.write().await = ReconnectionState::Idle;
+    }
+
+    /// The backoff before the given reconnect attempt: `retry_delay` doubled per attempt, capped at
+    /// [`MAX_RETRY_DELAY_MS`], randomized between half and the full delay so nodes don't reconnect in lockstep
+    pub fn reconnect_delay(&self, attempt: u32) -> Duration {
+        let base = self.options.retry_delay.unwrap_or(DEFAULT_RETRY_DELAY_MS);
+        let delay = base.saturating_mul(1u64 << attempt.saturating_sub(1).min(16)).min(MAX_RETRY_DELAY_MS);
+        Duration::from_millis(rand::random_range(delay / 2..=delay))
+    }
+
+    /// Runs one WebSocket connection until it is closed.
+    /// Returns the close reason, or an error when the connection could not be established.
+    async fn run_socket(&self, user_id: &str, client_name: &str, cancel: &CancellationToken) -> Result<String, String> {
+        let ws_url = format!("{}/v4/websocket", self.get_ws_url());
+        let mut request = ws_url.into_client_request().map_err(|e| e.to_string())?;
+
+        let headers = request.headers_mut();
+        headers.insert("Authorization", HeaderValue::from_str(&self.options.authorization).map_err(|e| e.to_string())?);
+        headers.insert("User-Id", HeaderValue::from_str(user_id).map_err(|e| e.to_string())?);
+        headers.insert("Client-Name", HeaderValue::from_str(client_name).map_err(|e| e.to_string())?);
+        if let Some(session_id) = self.session_id.read().await.clone() {
+            headers.insert("Session-Id", HeaderValue::from_str(&session_id).map_err(|e| e.to_string())?);
         }
-        
-        let url = format!("{}/v4/sessions/{}/players/{}", self.get_rest_url(), session.unwrap(), guild_id);
-        let req = self.reqwest_client
-            .delete(&url)
-            .header("Authorization", &self.options.authorization)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
-
-        if !req.status().is_success() && req.status() != 204 {
-            return Err(format!("Failed to destroy player ({})", req.status()));
+
+        let (ws_stream, _) = tokio::select! {
+            connection = connect_async(request) => connection.map_err(|e| e.to_string())?,
+            _ = cancel.cancelled() => return Err("Connecting was cancelled".to_string()),
+        };
+        info!("Connected to Lavalink Node {}", self.id);
+        *self.connected.write().await = true;
+        *self.reconnection_state.write().await = ReconnectionState::Idle;
+        let _ = self.event_sender.send(NodeMessage::Connected { node_id: self.id.clone() }).await;
+
+        let (mut write, mut read) = ws_stream.split();
+        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel();
+        *self.outgoing.lock().unwrap_or_else(PoisonError::into_inner) = Some(outgoing_tx);
+
+        let heart_beat_interval = self.options.heart_beat_interval.unwrap_or(DEFAULT_HEART_BEAT_INTERVAL_MS);
+        let mut heart_beat = (heart_beat_interval > 0).then(|| {
+            let period = Duration::from_millis(heart_beat_interval);
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval
+        });
+        // When the unanswered ping was sent
+        let mut ping_sent_at: Option<Instant> = None;
+        // Fetched alongside reading the socket, so a slow REST answer doesn't hold back the ready op
+        let info_fetch = self.fetch_info();
+        tokio::pin!(info_fetch);
+        let mut info_fetched = false;
+        let mut close_reason = "WebSocket stream ended".to_string();
+
+        loop {
+            // Biased, so a Pong which is already buffered is read before the heartbeat tick declares the ping unanswered
+            let msg = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => {
+                    let frame = CloseFrame { code: CloseCode::Normal, reason: "Node disconnected by the client".into() };
+                    if let Err(e) = write.send(Message::Close(Some(frame))).await {
+                        warn!("Failed to send the close frame to node {}: {}", self.id, e);
+                    }
+                    close_reason = "Disconnected by the client".to_string();
+                    break;
+                },
+                msg = read.next() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                info = &mut info_fetch, if !info_fetched => {
+                    info_fetched = true;
+                    match info {
+                        Ok(info) => info!(
+                            "Node {} runs {:?} {}",
+                            self.id,
+                            self.node_type().await,
+                            info.version.semver
+                        ),
+                        Err(e) => warn!("Failed to fetch the info of node {}, it is fetched again when needed: {}", self.id, e),
+                    }
+                    continue;
+                },
+                Some(outgoing) = outgoing_rx.recv() => {
+                    if let Err(e) = write.send(outgoing).await {
+                        warn!("Failed to send a message to node {}: {}", self.id, e);
+                    }
+                    continue;
+                },
+                _ = async {
+                    match heart_beat.as_mut() {
+                        Some(interval) => interval.tick().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    if ping_sent_at.is_some() {
+                        warn!("Node {} did not answer the last ping within {}ms, reconnecting", self.id, heart_beat_interval);
+                        self.debug(DebugEvents::SocketTerminateHeartBeatTimeout, format!("Node {} did not answer the last ping within {}ms, terminating the socket", self.id, heart_beat_interval)).await;
+                        close_reason = "Heartbeat timed out".to_string();
+                        break;
+                    }
+                    self.debug(DebugEvents::HeartBeatTriggered, format!("Sending a ping to node {}", self.id)).await;
+                    if let Err(e) = write.send(Message::Ping(Default::default())).await {
+                        warn!("Failed to send a ping to node {}: {}", self.id, e);
+                    }
+                    ping_sent_at = Some(Instant::now());
+                    continue;
+                },
+            };
+            match msg {
+                Ok(Message::Text(text)) => {
+                    debug!("Received WebSocket message: {}", text);
+                    match serde_json::from_str::<LavalinkMessage>(&text) {
+                        Ok(lavalink_msg) => {
+                            match &lavalink_msg {
+                                LavalinkMessage::Ready { session_id, .. } => {
+                                    *self.session_id.write().await = Some(session_id.clone());
+                                    info!("Lavalink Node {} is READY with session {}", self.id, session_id);
+                                },
+                                LavalinkMessage::Stats(stats) => {
+                                    *self.stats.write().await = Some(stats.clone());
+                                    self.record_stats(stats).await;
+                                },
+                                _ => {}
+                            }
+                            let _ = self.event_sender.send(NodeMessage::Payload { node_id: self.id.clone(), message: lavalink_msg }).await;
+                        },
+                        Err(e) => warn!("Failed to deserialize Lavalink message: {} | Data: {}", e, text),
+                    }
+                },
+                Ok(Message::Pong(_)) => {
+                    if let Some(sent_at) = ping_sent_at.take() {
+                        *self.ping.write().await = Some(sent_at.elapsed().as_millis() as i64);
+                    }
+                },
+                Ok(Message::Close(c)) => {
+                    warn!("WebSocket closed by node {}: {:?}", self.id, c);
+                    close_reason = c.map(|f| format!("Closed with code {}: {}", f.code, f.reason)).unwrap_or_else(|| "Closed by node".to_string());
+                    break;
+                },
+                Err(e) => {
+                    warn!("WebSocket error on node {}: {}", self.id, e);
+                    close_reason = e.to_string();
+                    break;
+                },
+                _ => {}
+            }
         }
-        
-        Ok(())
+        *self.outgoing.lock().unwrap_or_else(PoisonError::into_inner) = None;
+        *self.ping.write().await = None;
+        *self.connected.write().await = false;
+        Ok(close_reason)
     }
 
-    /// Load tracks / Search via loadtracks endpoint
-    pub async fn search(&self, query: &str) -> Result<SearchResult, String> {
-        let url = format!("{}/v4/loadtracks?identifier={}", self.get_rest_url(), urlencoding::encode(query));
-        
-        let req = self.reqwest_client
-            .get(&url)
-            .header("Authorization", &self.options.authorization)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
-            
-        if !req.status().is_success() {
-            let status = req.status();
-            let text = req.text().await.unwrap_or_default();
-            return Err(format!("Failed to load tracks: {} | {}", status, text));
+    async fn debug(&self, event: DebugEvents, message: String) {
+        let _ = self.event_sender.send(NodeMessage::Debug { event, message }).await;
+    }
+
+    /// A REST request to the node's `path` with the authorization header and timeout set
+    fn rest(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.reqwest_client
+            .request(method, format!("{}{}", self.get_rest_url(), path))
+            .header("Authorization", &self.options.authorization);
+        match self.options.request_timeout {
+            Some(timeout) => request.timeout(Duration::from_millis(timeout)),
+            None => request,
         }
-        
-        let body = req.text().await.map_err(|e| e.to_string())?;
-        serde_json::from_str::<SearchResult>(&body)
-            .map_err(|e| format!("Deserialize error: {} | body snippet: {}", e, &body[..body.len().min(300)]))
-    }
-
-    pub async fn check_status(&self) -> Result<String, String> {
-        let url = format!("{}/v4/info", self.get_rest_url());
-        let res = self.reqwest_client
-            .get(&url)
-            .header("Authorization", &self.options.authorization)
-            .send()
-            .await
-            .map_err(|e| e.to_string())?;
-        
-        res.text().await.map_err(|e| e.to_string())
-    }
-
-    pub async fn fetch_all_players(&self) -> Result<Vec<LavalinkPlayer>, String> {
-        let session = self.session_id.read().await.clone();
-        if session.is_none() {
-            return Err(format!("Node {} is not ready yet: No Session ID", self.id));
+    }
+
+    /// Sends a REST request. Unsuccessful responses fail with [`LavalinkError::Http`],
+    /// carrying Lavalink's error body when the node sent one.
+    async fn execute(&self, request: RequestBuilder) -> Result<Response, LavalinkError> {
+        let res = request.send().await?;
+        let status = res.status();
+        if status.is_success() {
+            return Ok(res);
+        }
+
+        let text = res.text().await.unwrap_or_default();
+        let body = serde_json::from_str::<InvalidLavalinkRestRequest>(&text).ok();
+        if body.is_none() {
+            debug!("Node {} answered {} with a non Lavalink error body: {}", self.id, status, text);
         }
-        let url = format!("{}/v4/sessions/{}/players", self.get_rest_url(), session.unwrap());
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<Vec<LavalinkPlayer>>().await.map_err(|e| e.to_string())
+        Err(LavalinkError::Http { status: status.as_u16(), body })
     }
 
-    pub async fn fetch_player(&self, guild_id: &str) -> Result<LavalinkPlayer, String> {
-        let session = self.session_id.read().await.clone();
-        if session.is_none() {
-            return Err(format!("Node {} is not ready yet: No Session ID", self.id));
+    /// Sends an idempotent REST request, retrying it `request_retries` times
+    /// after timeouts, connection errors and 5xx responses
+    async fn execute_idempotent(&self, request: RequestBuilder) -> Result<Response, LavalinkError> {
+        let retries = self.options.request_retries.unwrap_or(0);
+        let delay = Duration::from_millis(self.options.request_retry_delay.unwrap_or(DEFAULT_REQUEST_RETRY_DELAY_MS));
+
+        let mut attempt = 0;
+        loop {
+            // Requests with a streamed body can't be cloned and are only sent once
+            let Some(retry) = request.try_clone().filter(|_| attempt < retries) else {
+                return self.execute(request).await;
+            };
+
+            match self.execute(retry).await {
+                Err(e) if Self::is_retryable(&e) => {
+                    attempt += 1;
+                    debug!("Node {} REST request failed ({}), retry {}/{}", self.id, e, attempt, retries);
+                    tokio::time::sleep(delay).await;
+                }
+                res => return res,
+            }
         }
-        let url = format!("{}/v4/sessions/{}/players/{}", self.get_rest_url(), session.unwrap(), guild_id);
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<LavalinkPlayer>().await.map_err(|e| e.to_string())
     }
 
-    pub async fn update_session(&self, resuming: Option<bool>, timeout: Option<u64>) -> Result<serde_json::Value, String> {
-        let session = self.session_id.read().await.clone();
-        if session.is_none() {
-            return Err(format!("Node {} is not ready yet: No Session ID", self.id));
+    fn is_retryable(error: &LavalinkError) -> bool {
+        match error {
+            LavalinkError::Timeout => true,
+            LavalinkError::Request(e) => e.is_connect() || e.is_request(),
+            LavalinkError::Http { status, .. } => *status >= 500,
+            _ => false,
         }
-        let url = format!("{}/v4/sessions/{}", self.get_rest_url(), session.unwrap());
+    }
+
+    /// Deserializes a response, an empty body deserializes like `null`
+    async fn json<T: DeserializeOwned>(res: Response) -> Result<T, LavalinkError> {
+        let text = res.text().await?;
+        let text = if text.trim().is_empty() { "null" } else { text.as_str() };
+        serde_json::from_str(text)
+            .map_err(|e| LavalinkError::Decode(format!("{} | body snippet: {}", e, &text[..text.floor_char_boundary(300)])))
+    }
+
+    /// Update the Player on the Lavalink Server
+    pub async fn update_player(&self, guild_id: &str, no_replace: bool, update_data: &LavalinkPlayOptions) -> Result<LavalinkPlayer, LavalinkError> {
+        let session = self.require_session().await?;
+        let path = format!("/v4/sessions/{}/players/{}?noReplace={}", session, guild_id, no_replace);
+        Self::json(self.execute(self.rest(Method::PATCH, &path).json(update_data)).await?).await
+    }
+
+    /// Destroy the Player on the Lavalink Server
+    pub async fn destroy_player(&self, guild_id: &str) -> Result<(), LavalinkError> {
+        let session = self.require_session().await?;
+        let path = format!("/v4/sessions/{}/players/{}", session, guild_id);
+        self.execute(self.rest(Method::DELETE, &path)).await?;
+        Ok(())
+    }
+
+    /// Load tracks / Search via loadtracks endpoint
+    pub async fn search(&self, query: &str) -> Result<SearchResult, LavalinkError> {
+        let path = format!("/v4/loadtracks?identifier={}", urlencoding::encode(query));
+        Self::json(self.execute_idempotent(self.rest(Method::GET, &path)).await?).await
+    }
+
+    /// Search via the LavaSearch plugin's loadsearch endpoint, `None` when nothing was found
+    pub async fn lava_search(&self, query: &str, types: &[String]) -> Result<Option<LavaSearchResponse>, LavalinkError> {
+        let path = format!("/v4/loadsearch?query={}&types={}", urlencoding::encode(query), urlencoding::encode(&types.join(",")));
+        Self::json(self.execute_idempotent(self.rest(Method::GET, &path)).await?).await
+    }
+
+    pub async fn check_status(&self) -> Result<String, LavalinkError> {
+        Ok(self.execute_idempotent(self.rest(Method::GET, "/v4/info")).await?.text().await?)
+    }
+
+    pub async fn fetch_all_players(&self) -> Result<Vec<LavalinkPlayer>, LavalinkError> {
+        let session = self.require_session().await?;
+        Self::json(self.execute_idempotent(self.rest(Method::GET, &format!("/v4/sessions/{}/players", session))).await?).await
+    }
+
+    pub async fn fetch_player(&self, guild_id: &str) -> Result<LavalinkPlayer, LavalinkError> {
+        let session = self.require_session().await?;
+        Self::json(self.execute_idempotent(self.rest(Method::GET, &format!("/v4/sessions/{}/players/{}", session, guild_id))).await?).await
+    }
+
+    pub async fn update_session(&self, resuming: Option<bool>, timeout: Option<u64>) -> Result<serde_json::Value, LavalinkError> {
+        let session = self.require_session().await?;
         let mut body = serde_json::Map::new();
         if let Some(r) = resuming { body.insert("resuming".to_string(), serde_json::Value::Bool(r)); }
         if let Some(t) = timeout { body.insert("timeout".to_string(), serde_json::Value::Number(serde_json::Number::from(t))); }
-        let res = self.reqwest_client.patch(&url).header("Authorization", &self.options.authorization).json(&body).send().await.map_err(|e| e.to_string())?;
-        res.json::<serde_json::Value>().await.map_err(|e| e.to_string())
+        Self::json(self.execute(self.rest(Method::PATCH, &format!("/v4/sessions/{}", session)).json(&body)).await?).await
     }
 
-    pub async fn decode_single_track(&self, encoded: &str) -> Result<crate::types::track::LavalinkTrack, String> {
-        let url = format!("{}/v4/decodetrack?encodedTrack={}", self.get_rest_url(), urlencoding::encode(encoded));
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<crate::types::track::LavalinkTrack>().await.map_err(|e| e.to_string())
+    pub async fn decode_single_track(&self, encoded: &str) -> Result<crate::types::track::LavalinkTrack, LavalinkError> {
+        let path = format!("/v4/decodetrack?encodedTrack={}", urlencoding::encode(encoded));
+        Self::json(self.execute_idempotent(self.rest(Method::GET, &path)).await?).await
     }
 
-    pub async fn decode_multiple_tracks(&self, encodeds: Vec<String>) -> Result<Vec<crate::types::track::LavalinkTrack>, String> {
-        let url = format!("{}/v4/decodetracks", self.get_rest_url());
-        let res = self.reqwest_client.post(&url).header("Authorization", &self.options.authorization).json(&encodeds).send().await.map_err(|e| e.to_string())?;
-        res.json::<Vec<crate::types::track::LavalinkTrack>>().await.map_err(|e| e.to_string())
+    pub async fn decode_multiple_tracks(&self, encodeds: Vec<String>) -> Result<Vec<crate::types::track::LavalinkTrack>, LavalinkError> {
+        Self::json(self.execute_idempotent(self.rest(Method::POST, "/v4/decodetracks").json(&encodeds)).await?).await
     }
 
-    pub async fn get_lyrics(&self, track: &crate::types::track::Track, skip_track_source: bool) -> Result<serde_json::Value, String> {
+    pub async fn get_lyrics(&self, track: &crate::types::track::Track, skip_track_source: bool) -> Result<serde_json::Value, LavalinkError> {
         let encoded = track.encoded.as_deref().unwrap_or_default();
-        let url = format!("{}/v4/lyrics?track={}&skipTrackSource={}", self.get_rest_url(), encoded, skip_track_source);
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<serde_json::Value>().await.map_err(|e| e.to_string())
+        let path = format!("/v4/lyrics?track={}&skipTrackSource={}", urlencoding::encode(encoded), skip_track_source);
+        Self::json(self.execute(self.rest(Method::GET, &path)).await?).await
     }
 
-    pub async fn get_current_lyrics(&self, guild_id: &str, skip_track_source: bool) -> Result<serde_json::Value, String> {
-        let session = self.session_id.read().await.clone();
-        if session.is_none() { return Err("No Session".to_string()); }
-        let url = format!("{}/v4/sessions/{}/players/{}/track/lyrics?skipTrackSource={}", self.get_rest_url(), session.unwrap(), guild_id, skip_track_source);
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<serde_json::Value>().await.map_err(|e| e.to_string())
+    pub async fn get_current_lyrics(&self, guild_id: &str, skip_track_source: bool) -> Result<serde_json::Value, LavalinkError> {
+        let session = self.require_session().await?;
+        let path = format!("/v4/sessions/{}/players/{}/track/lyrics?skipTrackSource={}", session, guild_id, skip_track_source);
+        Self::json(self.execute(self.rest(Method::GET, &path)).await?).await
     }
 
-    pub async fn subscribe_lyrics(&self, guild_id: &str) -> Result<(), String> {
-        let session = self.session_id.read().await.clone();
-        if session.is_none() { return Err("No Session".to_string()); }
-        let url = format!("{}/v4/sessions/{}/players/{}/lyrics/subscribe", self.get_rest_url(), session.unwrap(), guild_id);
-        self.reqwest_client.post(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
+    pub async fn subscribe_lyrics(&self, guild_id: &str) -> Result<(), LavalinkError> {
+        let session = self.require_session().await?;
+        let path = format!("/v4/sessions/{}/players/{}/lyrics/subscribe", session, guild_id);
+        self.execute(self.rest(Method::POST, &path)).await?;
         Ok(())
     }
 
-    pub async fn unsubscribe_lyrics(&self, guild_id: &str) -> Result<(), String> {
-        let session = self.session_id.read().await.clone();
-        if session.is_none() { return Err("No Session".to_string()); }
-        let url = format!("{}/v4/sessions/{}/players/{}/lyrics/subscribe", self.get_rest_url(), session.unwrap(), guild_id);
-        self.reqwest_client.delete(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
+    pub async fn unsubscribe_lyrics(&self, guild_id: &str) -> Result<(), LavalinkError> {
+        let session = self.require_session().await?;
+        let path = format!("/v4/sessions/{}/players/{}/lyrics/subscribe", session, guild_id);
+        self.execute(self.rest(Method::DELETE, &path)).await?;
         Ok(())
     }
 
-    pub async fn fetch_stats(&self) -> Result<NodeStats, String> {
-        let url = format!("{}/v4/stats", self.get_rest_url());
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<NodeStats>().await.map_err(|e| e.to_string())
+    /// The SponsorBlock categories skipped in the guild's player, categories unknown to this client are left out
+    pub async fn get_sponsor_block(&self, guild_id: &str) -> Result<Vec<SponsorBlockSegment>, LavalinkError> {
+        self.require(NodeCapability::SponsorBlock).await?;
+        let session = self.require_session().await?;
+        let path = format!("/v4/sessions/{}/players/{}/sponsorblock/categories", session, guild_id);
+        let categories: Option<Vec<String>> = Self::json(self.execute_idempotent(self.rest(Method::GET, &path)).await?).await?;
+        Ok(categories.unwrap_or_default().iter().filter_map(|c| c.parse().ok()).collect())
+    }
+
+    pub async fn set_sponsor_block(&self, guild_id: &str, segments: &[SponsorBlockSegment]) -> Result<(), LavalinkError> {
+        self.require(NodeCapability::SponsorBlock).await?;
+        let session = self.require_session().await?;
+        let path = format!("/v4/sessions/{}/players/{}/sponsorblock/categories", session, guild_id);
+        self.execute(self.rest(Method::PUT, &path).json(segments)).await?;
+        Ok(())
     }
 
-    pub async fn fetch_connection_metrics(&self) -> Result<serde_json::Value, String> {
-        let url = format!("{}/v4/connection", self.get_rest_url());
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<serde_json::Value>().await.map_err(|e| e.to_string())
+    pub async fn delete_sponsor_block(&self, guild_id: &str) -> Result<(), LavalinkError> {
+        self.require(NodeCapability::SponsorBlock).await?;
+        let session = self.require_session().await?;
+        let path = format!("/v4/sessions/{}/players/{}/sponsorblock/categories", session, guild_id);
+        self.execute(self.rest(Method::DELETE, &path)).await?;
+        Ok(())
     }
 
-    pub async fn fetch_version(&self) -> Result<String, String> {
-        let url = format!("{}/version", self.get_rest_url());
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.text().await.map_err(|e| e.to_string())
+    pub async fn fetch_stats(&self) -> Result<NodeStats, LavalinkError> {
+        Self::json(self.execute_idempotent(self.rest(Method::GET, "/v4/stats")).await?).await
     }
 
-    pub async fn fetch_info(&self) -> Result<serde_json::Value, String> {
-        let url = format!("{}/v4/info", self.get_rest_url());
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<serde_json::Value>().await.map_err(|e| e.to_string())
+    pub async fn fetch_connection_metrics(&self) -> Result<serde_json::Value, LavalinkError> {
+        self.require(NodeCapability::ConnectionMetrics).await?;
+        Self::json(self.execute_idempotent(self.rest(Method::GET, "/v4/connection")).await?).await
     }
 
-    pub async fn route_planner_status(&self) -> Result<serde_json::Value, String> {
-        let url = format!("{}/v4/routeplanner/status", self.get_rest_url());
-        let res = self.reqwest_client.get(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        res.json::<serde_json::Value>().await.map_err(|e| e.to_string())
+    pub async fn fetch_version(&self) -> Result<String, LavalinkError> {
+        Ok(self.execute_idempotent(self.rest(Method::GET, "/version")).await?.text().await?)
     }
 
-    pub async fn route_planner_unmark_failed_address(&self, address: &str) -> Result<(), String> {
-        let url = format!("{}/v4/routeplanner/free/address", self.get_rest_url());
-        self.reqwest_client.post(&url).header("Authorization", &self.options.authorization).json(&serde_json::json!({ "address": address })).send().await.map_err(|e| e.to_string())?;
+    /// Fetches the node's info and updates the cached `info`
+    pub async fn fetch_info(&self) -> Result<LavalinkInfo, LavalinkError> {
+        let info: LavalinkInfo = Self::json(self.execute_idempotent(self.rest(Method::GET, "/v4/info")).await?).await?;
+        *self.info.write().await = Some(info.clone());
+        Ok(info)
+    }
+
+    pub async fn route_planner_status(&self) -> Result<serde_json::Value, LavalinkError> {
+        Self::json(self.execute_idempotent(self.rest(Method::GET, "/v4/routeplanner/status")).await?).await
+    }
+
+    pub async fn route_planner_unmark_failed_address(&self, address: &str) -> Result<(), LavalinkError> {
+        let request = self.rest(Method::POST, "/v4/routeplanner/free/address").json(&serde_json::json!({ "address": address }));
+        self.execute(request).await?;
         Ok(())
     }
 
-    pub async fn route_planner_unmark_all_failed_addresses(&self) -> Result<(), String> {
-        let url = format!("{}/v4/routeplanner/free/all", self.get_rest_url());
-        self.reqwest_client.post(&url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
+    pub async fn route_planner_unmark_all_failed_addresses(&self) -> Result<(), LavalinkError> {
+        self.execute(self.rest(Method::POST, "/v4/routeplanner/free/all")).await?;
         Ok(())
     }
 
-    pub async fn request(&self, method: reqwest::Method, path: &str) -> Result<serde_json::Value, String> {
-        let url = format!("{}{}", self.get_rest_url(), path);
-        let req = self.reqwest_client.request(method, &url).header("Authorization", &self.options.authorization).send().await.map_err(|e| e.to_string())?;
-        req.json::<serde_json::Value>().await.map_err(|e| e.to_string())
+    pub async fn request(&self, method: reqwest::Method, path: &str) -> Result<serde_json::Value, LavalinkError> {
+        Self::json(self.execute(self.rest(method, path)).await?).await
+    }
+
+    pub async fn request_with_body(&self, method: reqwest::Method, path: &str, body: &serde_json::Value) -> Result<serde_json::Value, LavalinkError> {
+        Self::json(self.execute(self.rest(method, path).json(body)).await?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_node(retry_delay: Option<u64>) -> LavalinkNode {
+        let (event_sender, _) = mpsc::channel(1);
+        LavalinkNode::new(LavalinkNodeOptions { id: "test".to_string(), retry_delay, ..Default::default() }, event_sender)
+    }
+
+    fn assert_delay_between(node: &LavalinkNode, attempt: u32, min_ms: u64, max_ms: u64) {
+        for _ in 0..50 {
+            let delay = node.reconnect_delay(attempt).as_millis() as u64;
+            assert!((min_ms..=max_ms).contains(&delay), "attempt {} waited {}ms, expected {} - {}ms", attempt, delay, min_ms, max_ms);
+        }
+    }
+
+    #[test]
+    fn reconnect_delay_doubles_per_attempt() {
+        let node = test_node(None);
+        assert_delay_between(&node, 0, 2_500, 5_000);
+        assert_delay_between(&node, 1, 2_500, 5_000);
+        assert_delay_between(&node, 2, 5_000, 10_000);
+        assert_delay_between(&node, 3, 10_000, 20_000);
+
+        let node = test_node(Some(1_000));
+        assert_delay_between(&node, 3, 2_000, 4_000);
     }
 
-    pub async fn request_with_body(&self, method: reqwest::Method, path: &str, body: &serde_json::Value) -> Result<serde_json::Value, String> {
-        let url = format!("{}{}", self.get_rest_url(), path);
-        let req = self.reqwest_client.request(method, &url).header("Authorization", &self.options.authorization).json(body).send().await.map_err(|e| e.to_string())?;
-        req.json::<serde_json::Value>().await.map_err(|e| e.to_string())
+    #[test]
+    fn reconnect_delay_is_capped() {
+        let node = test_node(None);
+        assert_delay_between(&node, 5, MAX_RETRY_DELAY_MS / 2, MAX_RETRY_DELAY_MS);
+        assert_delay_between(&node, u32::MAX, MAX_RETRY_DELAY_MS / 2, MAX_RETRY_DELAY_MS);
+
+        let node = test_node(Some(u64::MAX));
+        assert_delay_between(&node, 1, MAX_RETRY_DELAY_MS / 2, MAX_RETRY_DELAY_MS);
     }
 }
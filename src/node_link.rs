@@ -1,44 +1,48 @@
+use crate::error::LavalinkError;
 use crate::node::LavalinkNode;
 use crate::player::Player;
-use crate::types::track::{Track, UnresolvedTrack};
+use crate::types::track::Track;
 use crate::types::node_link::*;
 use crate::types::filters::*;
+use crate::types::node::NodeCapability;
 use async_trait::async_trait;
 
+/// NodeLink only routes, every method fails with [`LavalinkError::Unsupported`] on other servers
 #[async_trait]
 pub trait NodeLinkExt {
-    async fn add_mixer_layer(&self, player: &Player, track_to_add: &Track, volume: i32) -> Result<AddMixerLayerResponse, String>;
-    async fn list_mixer_layers(&self, player: &Player) -> Result<ListMixerLayersResponse, String>;
-    async fn update_mixer_layer_volume(&self, player: &Player, mix_id: &str, volume: i32) -> Result<bool, String>;
-    async fn remove_mixer_layer(&self, player: &Player, mix_id: &str) -> Result<bool, String>;
+    async fn add_mixer_layer(&self, player: &Player, track_to_add: &Track, volume: i32) -> Result<AddMixerLayerResponse, LavalinkError>;
+    async fn list_mixer_layers(&self, player: &Player) -> Result<ListMixerLayersResponse, LavalinkError>;
+    async fn update_mixer_layer_volume(&self, player: &Player, mix_id: &str, volume: i32) -> Result<bool, LavalinkError>;
+    async fn remove_mixer_layer(&self, player: &Player, mix_id: &str) -> Result<bool, LavalinkError>;
     
-    // NodeLink filters
-    async fn apply_echo_filter(&self, player: &mut Player, options: NodeLinkEchoFilter, disable: bool) -> Result<bool, String>;
-    async fn apply_chorus_filter(&self, player: &mut Player, options: NodeLinkChorusFilter, disable: bool) -> Result<bool, String>;
-    async fn apply_compressor_filter(&self, player: &mut Player, options: NodeLinkCompressorFilter, disable: bool) -> Result<bool, String>;
-    async fn apply_high_pass_filter(&self, player: &mut Player, options: NodeLinkHighPassFilter, disable: bool) -> Result<bool, String>;
-    async fn apply_phaser_filter(&self, player: &mut Player, options: NodeLinkPhaserFilter, disable: bool) -> Result<bool, String>;
-    async fn apply_spatial_filter(&self, player: &mut Player, options: NodeLinkSpatialFilter, disable: bool) -> Result<bool, String>;
-    async fn reset_node_link_filters(&self, player: &mut Player) -> Result<bool, String>;
-
-    async fn node_link_lyrics(&self, player: &Player, track: Option<&Track>, language: &str) -> Result<NodeLinkLyrics, String>;
-    async fn get_chapters(&self, player: &Player, track: Option<&Track>) -> Result<Vec<NodeLinkChapter>, String>;
+    // NodeLink filters, sent to the player's node so its capabilities are checked
+    async fn apply_echo_filter(&self, player: &mut Player, options: NodeLinkEchoFilter, disable: bool) -> Result<bool, LavalinkError>;
+    async fn apply_chorus_filter(&self, player: &mut Player, options: NodeLinkChorusFilter, disable: bool) -> Result<bool, LavalinkError>;
+    async fn apply_compressor_filter(&self, player: &mut Player, options: NodeLinkCompressorFilter, disable: bool) -> Result<bool, LavalinkError>;
+    async fn apply_high_pass_filter(&self, player: &mut Player, options: NodeLinkHighPassFilter, disable: bool) -> Result<bool, LavalinkError>;
+    async fn apply_phaser_filter(&self, player: &mut Player, options: NodeLinkPhaserFilter, disable: bool) -> Result<bool, LavalinkError>;
+    async fn apply_spatial_filter(&self, player: &mut Player, options: NodeLinkSpatialFilter, disable: bool) -> Result<bool, LavalinkError>;
+    async fn reset_node_link_filters(&self, player: &mut Player) -> Result<bool, LavalinkError>;
+
+    async fn node_link_lyrics(&self, player: &Player, track: Option<&Track>, language: &str) -> Result<NodeLinkLyrics, LavalinkError>;
+    async fn get_chapters(&self, player: &Player, track: Option<&Track>) -> Result<Vec<NodeLinkChapter>, LavalinkError>;
     
-    async fn get_connection_metrics(&self) -> Result<ConnectionMetricsResponse, String>;
-    async fn get_direct_stream(&self, track: &Track) -> Result<DirectStreamResponse, String>;
+    async fn get_connection_metrics(&self) -> Result<ConnectionMetricsResponse, LavalinkError>;
+    async fn get_direct_stream(&self, track: &Track) -> Result<DirectStreamResponse, LavalinkError>;
     
-    async fn change_audio_track_language(&self, player: &Player, language_audio_track_id: &str) -> Result<serde_json::Value, String>;
+    async fn change_audio_track_language(&self, player: &Player, language_audio_track_id: &str) -> Result<serde_json::Value, LavalinkError>;
     
-    async fn update_youtube_config(&self, refresh_token: Option<&str>, visitor_data: Option<&str>) -> Result<serde_json::Value, String>;
-    async fn get_youtube_config(&self, validate: bool) -> Result<serde_json::Value, String>;
-    async fn get_youtube_oauth(&self, refresh_token: &str) -> Result<YoutubeOAuthResponse, String>;
-    async fn update_youtube_oauth(&self, refresh_token: &str) -> Result<YoutubeOAuthResponse, String>;
+    async fn update_youtube_config(&self, refresh_token: Option<&str>, visitor_data: Option<&str>) -> Result<serde_json::Value, LavalinkError>;
+    async fn get_youtube_config(&self, validate: bool) -> Result<serde_json::Value, LavalinkError>;
+    async fn get_youtube_oauth(&self, refresh_token: &str) -> Result<YoutubeOAuthResponse, LavalinkError>;
+    async fn update_youtube_oauth(&self, refresh_token: &str) -> Result<YoutubeOAuthResponse, LavalinkError>;
 }
 
 #[async_trait]
 impl NodeLinkExt for LavalinkNode {
-    async fn add_mixer_layer(&self, player: &Player, track_to_add: &Track, volume: i32) -> Result<AddMixerLayerResponse, String> {
-        let sid = self.session_id.read().await.clone().ok_or("No session ID")?;
+    async fn add_mixer_layer(&self, player: &Player, track_to_add: &Track, volume: i32) -> Result<AddMixerLayerResponse, LavalinkError> {
+        self.require(NodeCapability::Mixer).await?;
+        let sid = self.require_session().await?;
         let vol_str = format!("{:.2}", (volume as f64) / 100.0);
         let body = serde_json::json!({
             "track": {
@@ -50,18 +54,20 @@ impl NodeLinkExt for LavalinkNode {
         
         let path = format!("/v4/sessions/{}/players/{}/mix", sid, player.guild_id);
         let res = self.request_with_body(reqwest::Method::POST, &path, &body).await?;
-        serde_json::from_value(res).map_err(|e| e.to_string())
+        Ok(serde_json::from_value(res)?)
     }
 
-    async fn list_mixer_layers(&self, player: &Player) -> Result<ListMixerLayersResponse, String> {
-        let sid = self.session_id.read().await.clone().ok_or("No session ID")?;
+    async fn list_mixer_layers(&self, player: &Player) -> Result<ListMixerLayersResponse, LavalinkError> {
+        self.require(NodeCapability::Mixer).await?;
+        let sid = self.require_session().await?;
         let path = format!("/v4/sessions/{}/players/{}/mix", sid, player.guild_id);
         let res = self.request(reqwest::Method::GET, &path).await?;
-        serde_json::from_value(res).map_err(|e| e.to_string())
+        Ok(serde_json::from_value(res)?)
     }
 
-    async fn update_mixer_layer_volume(&self, player: &Player, mix_id: &str, volume: i32) -> Result<bool, String> {
-        let sid = self.session_id.read().await.clone().ok_or("No session ID")?;
+    async fn update_mixer_layer_volume(&self, player: &Player, mix_id: &str, volume: i32) -> Result<bool, LavalinkError> {
+        self.require(NodeCapability::Mixer).await?;
+        let sid = self.require_session().await?;
         let vol_str = format!("{:.2}", (volume as f64) / 100.0);
         let body = serde_json::json!({ "volume": vol_str });
         let path = format!("/v4/sessions/{}/players/{}/mix/{}", sid, player.guild_id, mix_id);
@@ -69,108 +75,122 @@ impl NodeLinkExt for LavalinkNode {
         Ok(true)
     }
 
-    async fn remove_mixer_layer(&self, player: &Player, mix_id: &str) -> Result<bool, String> {
-        let sid = self.session_id.read().await.clone().ok_or("No session ID")?;
+    async fn remove_mixer_layer(&self, player: &Player, mix_id: &str) -> Result<bool, LavalinkError> {
+        self.require(NodeCapability::Mixer).await?;
+        let sid = self.require_session().await?;
         let path = format!("/v4/sessions/{}/players/{}/mix/{}", sid, player.guild_id, mix_id);
         self.request(reqwest::Method::DELETE, &path).await?;
         Ok(true)
     }
 
-    async fn apply_echo_filter(&self, player: &mut Player, options: NodeLinkEchoFilter, disable: bool) -> Result<bool, String> {
+    async fn apply_echo_filter(&self, player: &mut Player, options: NodeLinkEchoFilter, disable: bool) -> Result<bool, LavalinkError> {
+        player.node.require(NodeCapability::NodeLinkFilters).await?;
         if disable {
-            player.filter_manager.data.echo = None;
+            player.filter_manager.clear_echo();
         } else {
-            player.filter_manager.data.echo = Some(options);
+            player.filter_manager.set_echo(options);
         }
-        // In reality we'd apply the filter payload here to REST
+        player.apply_filters().await?;
         Ok(!disable)
     }
 
-    async fn apply_chorus_filter(&self, player: &mut Player, options: NodeLinkChorusFilter, disable: bool) -> Result<bool, String> {
+    async fn apply_chorus_filter(&self, player: &mut Player, options: NodeLinkChorusFilter, disable: bool) -> Result<bool, LavalinkError> {
+        player.node.require(NodeCapability::NodeLinkFilters).await?;
         if disable {
-            player.filter_manager.data.chorus = None;
+            player.filter_manager.clear_chorus();
         } else {
-            player.filter_manager.data.chorus = Some(options);
+            player.filter_manager.set_chorus(options);
         }
+        player.apply_filters().await?;
         Ok(!disable)
     }
 
-    async fn apply_compressor_filter(&self, player: &mut Player, options: NodeLinkCompressorFilter, disable: bool) -> Result<bool, String> {
+    async fn apply_compressor_filter(&self, player: &mut Player, options: NodeLinkCompressorFilter, disable: bool) -> Result<bool, LavalinkError> {
+        player.node.require(NodeCapability::NodeLinkFilters).await?;
         if disable {
-            player.filter_manager.data.compressor = None;
+            player.filter_manager.clear_compressor();
         } else {
-            player.filter_manager.data.compressor = Some(options);
+            player.filter_manager.set_compressor(options);
         }
+        player.apply_filters().await?;
         Ok(!disable)
     }
 
-    async fn apply_high_pass_filter(&self, player: &mut Player, options: NodeLinkHighPassFilter, disable: bool) -> Result<bool, String> {
+    async fn apply_high_pass_filter(&self, player: &mut Player, options: NodeLinkHighPassFilter, disable: bool) -> Result<bool, LavalinkError> {
+        player.node.require(NodeCapability::NodeLinkFilters).await?;
         if disable {
-            player.filter_manager.data.high_pass = None;
+            player.filter_manager.clear_high_pass();
         } else {
-            player.filter_manager.data.high_pass = Some(options);
+            player.filter_manager.set_high_pass(options);
         }
+        player.apply_filters().await?;
         Ok(!disable)
     }
 
-    async fn apply_phaser_filter(&self, player: &mut Player, options: NodeLinkPhaserFilter, disable: bool) -> Result<bool, String> {
+    async fn apply_phaser_filter(&self, player: &mut Player, options: NodeLinkPhaserFilter, disable: bool) -> Result<bool, LavalinkError> {
+        player.node.require(NodeCapability::NodeLinkFilters).await?;
         if disable {
-            player.filter_manager.data.phaser = None;
+            player.filter_manager.clear_phaser();
         } else {
-            player.filter_manager.data.phaser = Some(options);
+            player.filter_manager.set_phaser(options);
         }
+        player.apply_filters().await?;
         Ok(!disable)
     }
 
-    async fn apply_spatial_filter(&self, player: &mut Player, options: NodeLinkSpatialFilter, disable: bool) -> Result<bool, String> {
+    async fn apply_spatial_filter(&self, player: &mut Player, options: NodeLinkSpatialFilter, disable: bool) -> Result<bool, LavalinkError> {
+        player.node.require(NodeCapability::NodeLinkFilters).await?;
         if disable {
-            player.filter_manager.data.spatial = None;
+            player.filter_manager.clear_spatial();
         } else {
-            player.filter_manager.data.spatial = Some(options);
+            player.filter_manager.set_spatial(options);
         }
+        player.apply_filters().await?;
         Ok(!disable)
     }
 
-    async fn reset_node_link_filters(&self, player: &mut Player) -> Result<bool, String> {
-        player.filter_manager.data.spatial = None;
-        player.filter_manager.data.echo = None;
-        player.filter_manager.data.chorus = None;
-        player.filter_manager.data.compressor = None;
-        player.filter_manager.data.high_pass = None;
-        player.filter_manager.data.phaser = None;
+    async fn reset_node_link_filters(&self, player: &mut Player) -> Result<bool, LavalinkError> {
+        player.node.require(NodeCapability::NodeLinkFilters).await?;
+        player.filter_manager.clear_node_link_filters();
+        player.apply_filters().await?;
         Ok(true)
     }
 
-    async fn node_link_lyrics(&self, player: &Player, track: Option<&Track>, language: &str) -> Result<NodeLinkLyrics, String> {
-        let sid = self.session_id.read().await.clone().ok_or("No session ID")?;
+    async fn node_link_lyrics(&self, player: &Player, track: Option<&Track>, language: &str) -> Result<NodeLinkLyrics, LavalinkError> {
+        self.require(NodeCapability::NodeLinkLyrics).await?;
+        let sid = self.require_session().await?;
         let encoded = track.and_then(|t| t.encoded.clone()).unwrap_or_default();
         let path = format!("/v4/sessions/{}/players/{}/lyrics?encodedTrack={}&lang={}", sid, player.guild_id, encoded, language);
         let res = self.request(reqwest::Method::GET, &path).await?;
-        serde_json::from_value(res).map_err(|e| e.to_string())
+        Ok(serde_json::from_value(res)?)
     }
 
-    async fn get_chapters(&self, player: &Player, track: Option<&Track>) -> Result<Vec<NodeLinkChapter>, String> {
-        let sid = self.session_id.read().await.clone().ok_or("No session ID")?;
+    async fn get_chapters(&self, player: &Player, track: Option<&Track>) -> Result<Vec<NodeLinkChapter>, LavalinkError> {
+        self.require(NodeCapability::NodeLinkLyrics).await?;
+        let sid = self.require_session().await?;
         let encoded = track.and_then(|t| t.encoded.clone()).unwrap_or_default();
         let path = format!("/v4/sessions/{}/players/{}/chapters?encodedTrack={}", sid, player.guild_id, encoded);
         let res = self.request(reqwest::Method::GET, &path).await?;
-        serde_json::from_value(res).map_err(|e| e.to_string())
+        Ok(serde_json::from_value(res)?)
     }
 
-    async fn get_connection_metrics(&self) -> Result<ConnectionMetricsResponse, String> {
+    async fn get_connection_metrics(&self) -> Result<ConnectionMetricsResponse, LavalinkError> {
+        self.require(NodeCapability::ConnectionMetrics).await?;
         let res = self.request(reqwest::Method::GET, "/v4/connection").await?;
-        serde_json::from_value(res).map_err(|e| e.to_string())
+        Ok(serde_json::from_value(res)?)
     }
 
-    async fn get_direct_stream(&self, track: &Track) -> Result<DirectStreamResponse, String> {
+    async fn get_direct_stream(&self, track: &Track) -> Result<DirectStreamResponse, LavalinkError> {
+        self.require(NodeCapability::DirectStream).await?;
         let encoded = track.encoded.clone().unwrap_or_default();
         let path = format!("/v4/trackstream?encodedTrack={}", encoded);
         let res = self.request(reqwest::Method::GET, &path).await?;
-        serde_json::from_value(res).map_err(|e| e.to_string())
+        Ok(serde_json::from_value(res)?)
     }
 
-    async fn change_audio_track_language(&self, player: &Player, language_audio_track_id: &str) -> Result<serde_json::Value, String> {
-        let sid = self.session_id.read().await.clone().ok_or("No session ID")?;
+    async fn change_audio_track_language(&self, player: &Player, language_audio_track_id: &str) -> Result<serde_json::Value, LavalinkError> {
+        self.require(NodeCapability::AudioTrackLanguage).await?;
+        let sid = self.require_session().await?;
         let body = serde_json::json!({
             "track": {
                 "encoded": player.queue.current.as_ref().and_then(|t| t.encoded.clone()),
@@ -182,7 +202,8 @@ impl NodeLinkExt for LavalinkNode {
         self.request_with_body(reqwest::Method::PATCH, &path, &body).await
     }
 
-    async fn update_youtube_config(&self, refresh_token: Option<&str>, visitor_data: Option<&str>) -> Result<serde_json::Value, String> {
+    async fn update_youtube_config(&self, refresh_token: Option<&str>, visitor_data: Option<&str>) -> Result<serde_json::Value, LavalinkError> {
+        self.require(NodeCapability::YoutubeConfig).await?;
         let body = serde_json::json!({
             "refreshToken": refresh_token,
             "visitorData": visitor_data
@@ -190,20 +211,23 @@ impl NodeLinkExt for LavalinkNode {
         self.request_with_body(reqwest::Method::PATCH, "/v4/youtube/config", &body).await
     }
 
-    async fn get_youtube_config(&self, validate: bool) -> Result<serde_json::Value, String> {
+    async fn get_youtube_config(&self, validate: bool) -> Result<serde_json::Value, LavalinkError> {
+        self.require(NodeCapability::YoutubeConfig).await?;
         let path = if validate { "/v4/youtube/config?validate=true" } else { "/v4/youtube/config" };
         self.request(reqwest::Method::GET, path).await
     }
 
-    async fn get_youtube_oauth(&self, refresh_token: &str) -> Result<YoutubeOAuthResponse, String> {
+    async fn get_youtube_oauth(&self, refresh_token: &str) -> Result<YoutubeOAuthResponse, LavalinkError> {
+        self.require(NodeCapability::YoutubeConfig).await?;
         let path = format!("/v4/youtube/oauth?refreshToken={}", refresh_token);
         let res = self.request(reqwest::Method::GET, &path).await?;
-        serde_json::from_value(res).map_err(|e| e.to_string())
+        Ok(serde_json::from_value(res)?)
     }
 
-    async fn update_youtube_oauth(&self, refresh_token: &str) -> Result<YoutubeOAuthResponse, String> {
+    async fn update_youtube_oauth(&self, refresh_token: &str) -> Result<YoutubeOAuthResponse, LavalinkError> {
+        self.require(NodeCapability::YoutubeConfig).await?;
         let body = serde_json::json!({ "refreshToken": refresh_token });
         let res = self.request_with_body(reqwest::Method::POST, "/v4/youtube/oauth", &body).await?;
-        serde_json::from_value(res).map_err(|e| e.to_string())
+        Ok(serde_json::from_value(res)?)
     }
 }
@@ -1,25 +1,85 @@
-use crate::node::{LavalinkNode, LavalinkNodeOptions};
-use crate::types::events::LavalinkMessage;
+use crate::node::{LavalinkNode, LavalinkNodeOptions, NodeStats};
+use crate::constants::DestroyReasons;
+use crate::error::LavalinkError;
+use crate::types::events::NodeMessage;
+use crate::types::node::SessionStore;
+use crate::types::node_link::{HealthStatusThresholdOptions, NodeHealthSummary};
+use async_trait::async_trait;
 use std::collections::HashMap;
-use tokio::sync::mpsc;
-use std::sync::Arc;
+use std::path::PathBuf;
+use tokio::sync::{Mutex, mpsc};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+/// How the NodeManager ranks nodes when picking one for a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeSortStrategy {
+    /// Fewest players
+    Players,
+    /// Fewest playing players
+    PlayingPlayers,
+    /// Lowest lavalink cpu load
+    CpuLavalink,
+    /// Lowest used memory
+    Memory,
+    /// Fewest deficit and nulled frames
+    FrameDeficit,
+    /// Lowest Lavalink penalty score, see [`NodeStats::penalties`]
+    #[default]
+    Penalties,
+}
+
+impl NodeSortStrategy {
+    /// The score of a node with the given stats, lower is better. Nodes without stats yet score 0.
+    pub fn score(&self, stats: Option<&NodeStats>) -> f64 {
+        let Some(stats) = stats else { return 0.0 };
+        match self {
+            Self::Players => stats.players as f64,
+            Self::PlayingPlayers => stats.playing_players as f64,
+            Self::CpuLavalink => stats.cpu.lavalink_load,
+            Self::Memory => stats.memory.used as f64,
+            Self::FrameDeficit => stats.frame_deficit() as f64,
+            Self::Penalties => stats.penalties(),
+        }
+    }
+}
+
+/// Holds the nodes, cloning it is cheap and every clone shares the same nodes
+#[derive(Clone)]
 pub struct NodeManager {
-    pub nodes: HashMap<String, Arc<LavalinkNode>>,
-    pub event_sender: mpsc::Sender<LavalinkMessage>,
+    nodes: Arc<RwLock<HashMap<String, Arc<LavalinkNode>>>>,
+    pub event_sender: mpsc::Sender<NodeMessage>,
+    pub sort_strategy: NodeSortStrategy,
+    /// Where session ids are loaded from when adding a node without one, only set when resuming is enabled
+    pub session_store: Option<Arc<dyn SessionStore>>,
 }
 
 impl NodeManager {
-    pub fn new(event_sender: mpsc::Sender<LavalinkMessage>) -> Self {
+    pub fn new(event_sender: mpsc::Sender<NodeMessage>, sort_strategy: NodeSortStrategy, session_store: Option<Arc<dyn SessionStore>>) -> Self {
         Self {
-            nodes: HashMap::new(),
+            nodes: Arc::new(RwLock::new(HashMap::new())),
             event_sender,
+            sort_strategy,
+            session_store,
         }
     }
 
-    pub async fn add_node(&mut self, options: LavalinkNodeOptions, user_id: String, client_name: String) -> Result<(), String> {
-        if self.nodes.contains_key(&options.id) {
-            return Err(format!("Node with id {} already exists", options.id));
+    // The map is never locked across an await, so a poisoned lock still holds consistent data
+    fn read_nodes(&self) -> RwLockReadGuard<'_, HashMap<String, Arc<LavalinkNode>>> {
+        self.nodes.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_nodes(&self) -> RwLockWriteGuard<'_, HashMap<String, Arc<LavalinkNode>>> {
+        self.nodes.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds and connects a node. Without an explicit `session_id` the stored one is used to resume the previous session.
+    pub async fn add_node(&self, mut options: LavalinkNodeOptions, user_id: String, client_name: String) -> Result<(), LavalinkError> {
+        if self.read_nodes().contains_key(&options.id) {
+            return Err(LavalinkError::NodeAlreadyExists { node_id: options.id });
+        }
+        if options.session_id.is_none()
+            && let Some(store) = &self.session_store {
+            options.session_id = store.get(&options.id).await;
         }
         
         let id = options.id.clone();
@@ -27,20 +87,173 @@ impl NodeManager {
         
         LavalinkNode::connect(node.clone(), user_id, client_name).await?;
         
-        self.nodes.insert(id, node);
+        self.write_nodes().insert(id, node);
         Ok(())
     }
 
     pub fn get_node(&self, id: &str) -> Option<Arc<LavalinkNode>> {
-        self.nodes.get(id).cloned()
+        self.read_nodes().get(id).cloned()
+    }
+
+    /// All registered nodes, connected or not
+    pub fn nodes(&self) -> Vec<Arc<LavalinkNode>> {
+        self.read_nodes().values().cloned().collect()
+    }
+
+    /// Removes the node and shuts it down, its players are moved (with `auto_move`) or destroyed with `DestroyReasons::NodeDeleted`
+    pub async fn remove_node(&self, id: &str) -> bool {
+        let node = self.write_nodes().remove(id);
+        match node {
+            Some(node) => {
+                node.shutdown(DestroyReasons::NodeDeleted).await;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// The health of every node, for dashboards and alerting
+    pub async fn health_summary(&self, thresholds: &HealthStatusThresholdOptions) -> NodeHealthSummary {
+        let mut summary = NodeHealthSummary {
+            total_nodes: 0,
+            healthy_nodes: 0,
+            degraded_nodes: 0,
+            unhealthy_nodes: 0,
+            offline_nodes: 0,
+            overloaded_nodes: 0,
+            total_players: 0,
+            total_playing_players: 0,
+            average_penalty_score: 0.0,
+            nodes: HashMap::new(),
+        };
+        let mut penalty_sum = 0.0;
+
+        for node in self.nodes() {
+            let health = node.health_status(thresholds).await;
+            summary.total_nodes += 1;
+            match health.status.as_str() {
+                "healthy" => summary.healthy_nodes += 1,
+                "degraded" => summary.degraded_nodes += 1,
+                "unhealthy" => summary.unhealthy_nodes += 1,
+                _ => summary.offline_nodes += 1,
+            }
+            if health.is_overloaded {
+                summary.overloaded_nodes += 1;
+            }
+            if health.status != "offline" {
+                penalty_sum += health.penalty_score;
+            }
+            summary.total_players += health.metrics.players as i64;
+            summary.total_playing_players += health.metrics.playing_players as i64;
+            summary.nodes.insert(node.id.clone(), health);
+        }
+
+        let online_nodes = summary.total_nodes - summary.offline_nodes;
+        if online_nodes > 0 {
+            summary.average_penalty_score = penalty_sum / online_nodes as f64;
+        }
+        summary
+    }
+
+    /// All connected nodes with a session, sorted from least to most used by the given strategy
+    pub async fn least_used_nodes(&self, strategy: NodeSortStrategy) -> Vec<Arc<LavalinkNode>> {
+        let nodes = self.nodes();
+        let mut scored = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            if !*node.connected.read().await || node.session_id.read().await.is_none() {
+                continue;
+            }
+            let score = strategy.score(node.stats.read().await.as_ref());
+            scored.push((score, node));
+        }
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        scored.into_iter().map(|(_, node)| node).collect()
+    }
+    /// The least used node by the configured sort strategy
+    pub async fn least_used_node(&self) -> Option<Arc<LavalinkNode>> {
+        self.least_used_nodes(self.sort_strategy).await.into_iter().next()
+    }
+
+    /// The least used node serving the given voice region, falls back to [`NodeManager::least_used_node`]
+    /// when there is no region or no connected node serves it
+    pub async fn least_used_node_in_region(&self, region: Option<&str>) -> Option<Arc<LavalinkNode>> {
+        let nodes = self.least_used_nodes(self.sort_strategy).await;
+        region
+            .and_then(|region| nodes.iter().find(|node| node.serves_region(region)).cloned())
+            .or_else(|| nodes.into_iter().next())
+    }
+}
+
+/// Keeps the session ids in memory, enough to resume after reconnects but not after a restart
+#[derive(Default)]
+pub struct DefaultSessionStore {
+    data: tokio::sync::RwLock<HashMap<String, String>>,
+}
+
+impl DefaultSessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SessionStore for DefaultSessionStore {
+    async fn get(&self, node_id: &str) -> Option<String> {
+        self.data.read().await.get(node_id).cloned()
+    }
+
+    async fn set(&self, node_id: &str, session_id: &str) -> bool {
+        self.data.write().await.insert(node_id.to_string(), session_id.to_string());
+        true
+    }
+
+    async fn delete(&self, node_id: &str) -> bool {
+        self.data.write().await.remove(node_id).is_some()
+    }
+}
+
+/// Keeps the session ids in a JSON file (`{ "nodeId": "sessionId" }`), so sessions survive restarts
+pub struct FileSessionStore {
+    path: PathBuf,
+    // Serializes the read-modify-write cycles
+    lock: Mutex<()>,
+}
+
+impl FileSessionStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), lock: Mutex::new(()) }
+    }
+
+    async fn read(&self) -> HashMap<String, String> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    async fn write(&self, sessions: &HashMap<String, String>) -> bool {
+        let Ok(content) = serde_json::to_string_pretty(sessions) else { return false };
+        tokio::fs::write(&self.path, content).await.is_ok()
+    }
+}
+
+#[async_trait]
+impl SessionStore for FileSessionStore {
+    async fn get(&self, node_id: &str) -> Option<String> {
+        let _guard = self.lock.lock().await;
+        self.read().await.remove(node_id)
     }
 
-    pub fn remove_node(&mut self, id: &str) -> bool {
-        self.nodes.remove(id).is_some()
+    async fn set(&self, node_id: &str, session_id: &str) -> bool {
+        let _guard = self.lock.lock().await;
+        let mut sessions = self.read().await;
+        sessions.insert(node_id.to_string(), session_id.to_string());
+        self.write(&sessions).await
     }
 
-    pub fn least_used_node(&self) -> Option<Arc<LavalinkNode>> {
-        // Find node with fewest players. Simple stub.
-        self.nodes.values().next().cloned()
+    async fn delete(&self, node_id: &str) -> bool {
+        let _guard = self.lock.lock().await;
+        let mut sessions = self.read().await;
+        sessions.remove(node_id).is_some() && self.write(&sessions).await
     }
 }
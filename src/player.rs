@@ -1,15 +1,25 @@
-use crate::types::filters::FilterManager;
-use crate::types::player::{PlayerOptions, RepeatMode, PlayerPing, VoiceState, PlayOptions, LavalinkPlayOptions};
-use crate::types::events::{LavalinkMessage, SearchResult};
-use crate::utils::{LavaSearchQuery, SearchQuery};
+use crate::error::LavalinkError;
+use crate::types::filters::{AudioOutputs, EQBand, FilterManager, KaraokeFilter, TimescaleFilter};
+pub use crate::types::player::SponsorBlockSegment;
+use crate::types::player::{PlayerOptions, RepeatMode, PlayerPing, VoiceState, PlayOptions, LavalinkPlayOptions, LavalinkPlayerVoiceOptions, TrackPlayOptions};
+use crate::types::events::{ManagerEvent, SearchResult};
+use crate::utils::{LavaSearchQuery, ManagerUtils, SearchQuery, DEFAULT_SEARCH_PLATFORM};
+use crate::constants::LavalinkPlugins;
+use crate::types::utils::LavaSearchResponse;
 use crate::queue::Queue;
 use crate::types::queue::ManagerQueueOptions;
+use crate::types::manager::{AdvancedOptions, ManagerPlayerOptions};
+use crate::constants::DebugEvents;
 use crate::node::LavalinkNode;
-use crate::types::track::{Track, UnresolvedTrack};
+use crate::types::track::Track;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::broadcast;
 use serde_json::Value;
+use tracing::{debug, warn};
+
+/// The default of `AdvancedOptions::max_filter_fix_duration`, 8 minutes
+pub const DEFAULT_MAX_FILTER_FIX_DURATION_MS: u64 = 8 * 60 * 1000;
 
 #[derive(Clone)]
 pub struct Player {
@@ -28,29 +38,31 @@ pub struct Player {
     pub lavalink_volume: i32,
     pub last_position: i64,
     pub last_position_change: Option<i64>,
+    /// The playback speed since `last_position_change`, from the timescale which was last sent to the node
+    pub last_position_speed: f64,
     pub last_saved_position: i64,
+    /// The end time the current track was played with
+    pub end_time: Option<i64>,
     pub created_time_stamp: i64,
     pub connected: bool,
     pub voice_state: VoiceState,
+    /// The voice server credentials of the last voice server update, resent when changing the node
+    pub voice: Option<LavalinkPlayerVoiceOptions>,
     pub data: HashMap<String, Value>,
-    pub event_sender: mpsc::Sender<LavalinkMessage>,
-}
-
-pub enum SponsorBlockSegment {
-    Sponsor,
-    SelfPromo,
-    Interaction,
-    Intro,
-    Outro,
-    Preview,
-    MusicOfftopic,
-    Filler,
+    /// The manager's player options, see [`LavalinkManagerOptions::player_options`](crate::manager::LavalinkManagerOptions::player_options)
+    pub manager_options: ManagerPlayerOptions,
+    /// The manager's advanced options
+    pub advanced_options: AdvancedOptions,
+    pub event_sender: broadcast::Sender<ManagerEvent>,
 }
 
 impl Player {
-    pub fn new(options: PlayerOptions, node: Arc<LavalinkNode>, event_sender: mpsc::Sender<LavalinkMessage>, dont_emit: bool) -> Self {
+    pub fn new(options: PlayerOptions, node: Arc<LavalinkNode>, event_sender: broadcast::Sender<ManagerEvent>, dont_emit: bool) -> Self {
         let volume = options.volume.unwrap_or(100).clamp(0, 1000);
         let guild_id = options.guild_id.clone();
+        if !dont_emit {
+            let _ = event_sender.send(ManagerEvent::PlayerCreate { guild_id: guild_id.clone() });
+        }
         Self {
             filter_manager: FilterManager::new(),
             voice_channel_id: Some(options.voice_channel_id.clone()),
@@ -67,22 +79,113 @@ impl Player {
             lavalink_volume: volume,
             last_position: 0,
             last_position_change: None,
+            last_position_speed: 1.0,
             last_saved_position: 0,
+            end_time: None,
             created_time_stamp: chrono::Utc::now().timestamp_millis(),
             connected: false,
             voice_state: VoiceState::default(),
+            voice: None,
             data: HashMap::new(),
+            manager_options: ManagerPlayerOptions::default(),
+            advanced_options: AdvancedOptions::default(),
             event_sender,
         }
     }
 
+    /// Sets the manager's options and applies the volume decrementer / volume filter to the initial volume
+    pub(crate) fn with_manager_options(mut self, manager_options: ManagerPlayerOptions, advanced_options: AdvancedOptions) -> Self {
+        self.manager_options = manager_options;
+        self.advanced_options = advanced_options;
+        self.lavalink_volume = self.to_lavalink_volume(self.volume, false);
+        if self.volume_as_filter() {
+            // Sent with the first play
+            self.filter_manager.set_volume(self.lavalink_volume as f32 / 100.0);
+        }
+        self
+    }
+
+    /// Whether the volume is applied via the volume filter, `PlayerOptions::apply_volume_as_filter` overrides the manager's option
+    pub fn volume_as_filter(&self) -> bool {
+        self.options.apply_volume_as_filter.or(self.manager_options.apply_volume_as_filter).unwrap_or(false)
+    }
+
+    /// The volume the node applies for a player volume, scaled by the volume decrementer.
+    /// The volume filter goes up to 5.0, so it's capped at 500 instead of 1000 then.
+    fn to_lavalink_volume(&self, volume: i32, ignore_volume_decrementer: bool) -> i32 {
+        let volume = match self.manager_options.volume_decrementer {
+            Some(decrementer) if !ignore_volume_decrementer => (volume as f64 * decrementer).round() as i32,
+            _ => volume,
+        };
+        volume.clamp(0, if self.volume_as_filter() { 500 } else { 1000 })
+    }
+
+    /// Takes the volume over from the player's state on the node, e.g. after resuming. The user facing `volume`
+    /// is derived back from the volume decrementer, with the volume filter it's read from the filters.
+    pub(crate) fn restore_volume(&mut self, node_volume: i32) {
+        self.lavalink_volume = if self.volume_as_filter() {
+            self.filter_manager.data.volume.map_or(100, |v| (v * 100.0).round() as i32)
+        } else {
+            node_volume
+        };
+        self.volume = match self.manager_options.volume_decrementer {
+            Some(decrementer) if decrementer > 0.0 => (self.lavalink_volume as f64 / decrementer).round() as i32,
+            _ => self.lavalink_volume,
+        }.clamp(0, 1000);
+    }
+
+    /// Sets `volume` and `lavalink_volume` and returns the player volume to send, the volume filter is updated instead when it's used
+    fn update_volume(&mut self, volume: i32, ignore_volume_decrementer: bool) -> Option<i32> {
+        self.volume = volume.clamp(0, 1000);
+        self.lavalink_volume = self.to_lavalink_volume(self.volume, ignore_volume_decrementer);
+        if !self.volume_as_filter() {
+            return Some(self.lavalink_volume);
+        }
+        self.filter_manager.set_volume(self.lavalink_volume as f32 / 100.0);
+        self.debug(DebugEvents::PlayerVolumeAsFilter, format!("Applying volume {} of player {} as volume filter {}", self.volume, self.guild_id, self.lavalink_volume as f32 / 100.0));
+        None
+    }
+
+    /// The player volume of the node, 100 while the volume is applied via the filter
+    fn node_volume(&self) -> i32 {
+        if self.volume_as_filter() { 100 } else { self.lavalink_volume }
+    }
+
+    fn debug(&self, event: DebugEvents, message: String) {
+        debug!("[{}] {}", event.as_str(), message);
+        let _ = self.event_sender.send(ManagerEvent::Debug { event, message });
+    }
+
+    /// The estimated position in ms: the last position the node reported plus the time since then, scaled by the timescale.
+    /// It stays put while paused and never passes the end of the track or its `end_time`.
     pub fn position(&self) -> i64 {
-        if let Some(change) = chrono::Utc::now().timestamp_millis().checked_sub(self.last_position_change.unwrap_or(0)) {
-            if self.last_position_change.is_some() {
-                return self.last_position + change;
-            }
+        let mut position = self.last_position;
+        if let Some(change) = self.last_position_change && !self.paused {
+            let elapsed = (chrono::Utc::now().timestamp_millis() - change).max(0);
+            position += (elapsed as f64 * self.last_position_speed).round() as i64;
+        }
+        if let Some(track) = self.queue.current.as_ref().filter(|t| !t.info.is_stream && t.info.duration > 0) {
+            position = position.min(track.info.duration);
+        }
+        if let Some(end_time) = self.end_time {
+            position = position.min(end_time);
+        }
+        position.max(0)
+    }
+
+    /// Moves the estimated position into `last_position`, done periodically with `client_based_position_update_interval`
+    pub fn sync_position(&mut self) {
+        self.last_position = self.position();
+        if self.last_position_change.is_some() {
+            self.last_position_change = Some(chrono::Utc::now().timestamp_millis());
         }
-        self.last_position
+    }
+
+    /// Called after the filters were sent, the position reached so far was played with the previous timescale
+    fn filters_sent(&mut self) {
+        self.sync_position();
+        self.last_position_speed = self.filter_manager.playback_speed();
+        self.filter_manager.filter_updated_state = false;
     }
 
     pub fn set(&mut self, key: &str, value: Value) -> &mut Self {
@@ -103,8 +206,8 @@ impl Player {
         self.data.clone().into_iter().filter(|(k, _)| !k.starts_with("internal_")).collect()
     }
 
-    pub async fn play(&mut self, options: PlayOptions) -> Result<&mut Self, String> {
-        if let Some(_) = self.data.get("internal_queueempty") {
+    pub async fn play(&mut self, options: PlayOptions) -> Result<&mut Self, LavalinkError> {
+        if self.data.contains_key("internal_queueempty") {
             self.data.remove("internal_queueempty");
         }
 
@@ -118,31 +221,40 @@ impl Player {
             voice: None,
         };
 
-        if let Some(track_opt) = &options.track {
-            if track_opt.encoded.is_some() || track_opt.identifier.is_some() {
-                // If specific track is given, play it
-                final_options.track = Some(crate::types::player::TrackPlayOptions {
-                    encoded: track_opt.encoded.clone(),
-                    identifier: track_opt.identifier.clone(),
-                    user_data: track_opt.user_data.clone(),
-                    audio_track_id: track_opt.audio_track_id.clone(),
-                });
-                
-                if let Some(v) = options.volume {
-                    self.volume = v.clamp(0, 1000);
-                    self.lavalink_volume = self.volume;
-                    final_options.volume = Some(self.lavalink_volume);
-                }
-
-                final_options.position = options.position;
-                final_options.end_time = options.end_time;
-                final_options.paused = options.paused;
-                
-                let now = chrono::Utc::now().timestamp_millis();
-                self.node.update_player(&self.guild_id, options.no_replace.unwrap_or(false), &final_options).await?;
-                self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
-                return Ok(self);
+        if let Some(track_opt) = &options.track
+            && (track_opt.encoded.is_some() || track_opt.identifier.is_some()) {
+            // If specific track is given, play it
+            final_options.track = Some(TrackPlayOptions {
+                encoded: track_opt.encoded.clone(),
+                identifier: track_opt.identifier.clone(),
+                user_data: track_opt.user_data.clone(),
+                audio_track_id: track_opt.audio_track_id.clone(),
+            });
+            
+            if let Some(v) = options.volume {
+                self.update_volume(v, false);
+            }
+            // Always send the node volume, so the initial / decremented volume reaches the node
+            final_options.volume = Some(self.node_volume());
+            if self.filter_manager.filter_updated_state {
+                final_options.filters = Some(self.filter_manager.to_lavalink_filters());
+            }
+
+            final_options.position = options.position;
+            final_options.end_time = options.end_time;
+            final_options.paused = options.paused;
+            
+            let now = chrono::Utc::now().timestamp_millis();
+            self.node.update_player(&self.guild_id, options.no_replace.unwrap_or(false), &final_options).await?;
+            self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
+            if final_options.filters.is_some() {
+                self.filters_sent();
+            }
+            self.end_time = options.end_time;
+            if let Some(paused) = options.paused {
+                self.paused = paused;
             }
+            return Ok(self);
         }
 
         // Auto-shift from tracks if current is None
@@ -155,16 +267,18 @@ impl Player {
 
         // Play from queue
         if self.queue.current.is_none() {
-            return Err("There is no Track in the Queue, nor provided in the PlayOptions".to_string());
+            return Err(LavalinkError::InvalidState("There is no Track in the Queue, nor provided in the PlayOptions".to_string()));
         }
 
         if let Some(v) = options.volume {
-            self.volume = v.clamp(0, 1000);
-            self.lavalink_volume = self.volume;
-            final_options.volume = Some(self.lavalink_volume);
+            self.update_volume(v, false);
+        }
+        final_options.volume = Some(self.node_volume());
+        if self.filter_manager.filter_updated_state {
+            final_options.filters = Some(self.filter_manager.to_lavalink_filters());
         }
 
-        final_options.track = Some(crate::types::player::TrackPlayOptions {
+        final_options.track = Some(TrackPlayOptions {
             encoded: self.queue.current.as_ref().unwrap().encoded.clone(),
             identifier: None,
             user_data: self.queue.current.as_ref().unwrap().user_data.clone(),
@@ -172,55 +286,108 @@ impl Player {
         });
 
         final_options.position = options.position.or(Some(0));
-        // End time etc validation omitted for conciseness
+        final_options.end_time = options.end_time;
+        final_options.paused = options.paused;
         
         let now = chrono::Utc::now().timestamp_millis();
         self.node.update_player(&self.guild_id, options.no_replace.unwrap_or(false), &final_options).await?;
         self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
+        if final_options.filters.is_some() {
+            self.filters_sent();
+        }
+        self.end_time = options.end_time;
+        if let Some(paused) = options.paused {
+            self.paused = paused;
+        }
         self.playing = true;
         
         Ok(self)
     }
 
-    pub async fn set_volume(&mut self, volume: i32, _ignore_volume_decrementer: bool) -> Result<&mut Self, String> {
-        self.volume = volume.clamp(0, 1000);
-        self.lavalink_volume = self.volume;
-
+    /// Sets the volume (0 - 1000), scaled by the manager's `volume_decrementer` unless `ignore_volume_decrementer` is set.
+    /// With `apply_volume_as_filter` it's sent as the volume filter (`lavalink_volume / 100`) along with the other filters.
+    pub async fn set_volume(&mut self, volume: i32, ignore_volume_decrementer: bool) -> Result<&mut Self, LavalinkError> {
         let now = chrono::Utc::now().timestamp_millis();
-        let update_data = LavalinkPlayOptions {
-            volume: Some(self.lavalink_volume),
-            ..Default::default()
+        let update_data = match self.update_volume(volume, ignore_volume_decrementer) {
+            Some(volume) => LavalinkPlayOptions { volume: Some(volume), ..Default::default() },
+            None => LavalinkPlayOptions { filters: Some(self.filter_manager.to_lavalink_filters()), ..Default::default() },
         };
         self.node.update_player(&self.guild_id, false, &update_data).await?;
         self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
+        if update_data.filters.is_some() {
+            self.filters_sent();
+        }
         Ok(self)
     }
 
-    pub async fn lava_search(&self, query: LavaSearchQuery, request_user: Option<String>, throw_on_empty: bool) -> Result<SearchResult, String> {
-        self.node.search(&query.query).await
+    /// Searches via the LavaSearch plugin, which (like LavaSrc) has to be loaded on the node.
+    /// `None` when nothing was found.
+    pub async fn lava_search(&self, query: LavaSearchQuery, _request_user: Option<String>, _throw_on_empty: bool) -> Result<Option<LavaSearchResponse>, LavalinkError> {
+        let utils = ManagerUtils::new(DEFAULT_SEARCH_PLATFORM.to_string());
+        utils.validate_plugin(&self.node, LavalinkPlugins::LAVA_SEARCH).await?;
+        utils.validate_plugin(&self.node, LavalinkPlugins::LAVA_SRC).await?;
+
+        let query = utils.transform_lava_search_query(query);
+        let source = query.source.as_deref().unwrap_or(DEFAULT_SEARCH_PLATFORM);
+        utils.validate_query_string(&self.node, &query.query, Some(source)).await?;
+        utils.validate_source_string(&self.node, source).await?;
+
+        self.node.lava_search(&format!("{}:{}", source, query.query), &query.types).await
     }
 
-    pub async fn set_sponsor_block(&mut self, segments: Vec<SponsorBlockSegment>) -> Result<(), String> {
-        // Mock sponsorblock on node
+    /// Sets the SponsorBlock categories which are skipped in this player's tracks, needs the SponsorBlock plugin on the node
+    pub async fn set_sponsor_block(&mut self, segments: Vec<SponsorBlockSegment>) -> Result<(), LavalinkError> {
+        if segments.is_empty() {
+            return Err(LavalinkError::InvalidArgument("No segments provided, use delete_sponsor_block to disable SponsorBlock".to_string()));
+        }
+        let now = chrono::Utc::now().timestamp_millis();
+        self.node.set_sponsor_block(&self.guild_id, &segments).await?;
+        self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
+
+        let categories: Vec<_> = segments.iter().map(|s| s.as_str()).collect();
+        self.debug(DebugEvents::SetSponsorBlock, format!("Set the SponsorBlock categories of player {} to {}", self.guild_id, categories.join(", ")));
         Ok(())
     }
 
-    pub async fn get_sponsor_block(&self) -> Result<Vec<SponsorBlockSegment>, String> {
-        Ok(vec![])
+    /// The SponsorBlock categories which are skipped in this player's tracks
+    pub async fn get_sponsor_block(&self) -> Result<Vec<SponsorBlockSegment>, LavalinkError> {
+        self.node.get_sponsor_block(&self.guild_id).await
     }
 
-    pub async fn delete_sponsor_block(&mut self) -> Result<(), String> {
+    /// Stops skipping SponsorBlock segments in this player's tracks
+    pub async fn delete_sponsor_block(&mut self) -> Result<(), LavalinkError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.node.delete_sponsor_block(&self.guild_id).await?;
+        self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
+
+        self.debug(DebugEvents::DeleteSponsorBlock, format!("Deleted the SponsorBlock categories of player {}", self.guild_id));
         Ok(())
     }
 
-    pub async fn search(&self, query: SearchQuery, request_user: Option<String>, throw_on_empty: bool) -> Result<SearchResult, String> {
-        self.node.search(&query.query).await
+    /// Searches on the player's node. The source is `query.source` or a `source:` prefix of the query (default ytsearch),
+    /// the query and source are validated against the node's info first.
+    pub async fn search(&self, query: SearchQuery, _request_user: Option<String>, _throw_on_empty: bool) -> Result<SearchResult, LavalinkError> {
+        let utils = ManagerUtils::new(DEFAULT_SEARCH_PLATFORM.to_string());
+        let query = utils.transform_query(query);
+        let source = query.source.as_deref().unwrap_or(DEFAULT_SEARCH_PLATFORM);
+        utils.validate_query_string(&self.node, &query.query, Some(source)).await?;
+
+        let is_link = query.query.starts_with("http://") || query.query.starts_with("https://");
+        if is_link {
+            return self.node.search(&query.query).await;
+        }
+        utils.validate_source_string(&self.node, source).await?;
+        match source {
+            "http" | "https" | "link" | "uri" | "local" => self.node.search(&query.query).await,
+            _ => self.node.search(&format!("{}:{}", source, query.query)).await,
+        }
     }
 
-    pub async fn pause(&mut self) -> Result<&mut Self, String> {
+    pub async fn pause(&mut self) -> Result<&mut Self, LavalinkError> {
         if self.paused && !self.playing {
-            return Err("Player is already paused - not able to pause.".to_string());
+            return Err(LavalinkError::InvalidState("Player is already paused - not able to pause.".to_string()));
         }
+        self.last_position = self.position();
         self.paused = true;
         self.last_position_change = None;
 
@@ -234,11 +401,12 @@ impl Player {
         Ok(self)
     }
 
-    pub async fn resume(&mut self) -> Result<&mut Self, String> {
+    pub async fn resume(&mut self) -> Result<&mut Self, LavalinkError> {
         if !self.paused {
-            return Err("Player isn't paused - not able to resume.".to_string());
+            return Err(LavalinkError::InvalidState("Player isn't paused - not able to resume.".to_string()));
         }
         self.paused = false;
+        self.last_position_change = Some(chrono::Utc::now().timestamp_millis());
 
         let now = chrono::Utc::now().timestamp_millis();
         let update_data = LavalinkPlayOptions {
@@ -250,14 +418,14 @@ impl Player {
         Ok(self)
     }
 
-    pub async fn seek(&mut self, position: i64) -> Result<&mut Self, String> {
+    pub async fn seek(&mut self, position: i64) -> Result<&mut Self, LavalinkError> {
         if self.queue.current.is_none() {
             return Ok(self);
         }
         
         let position = position.max(0); // .min(current duration) handled implicitly or in future
 
-        self.last_position_change = Some(chrono::Utc::now().timestamp_millis());
+        self.last_position_change = if self.paused { None } else { Some(chrono::Utc::now().timestamp_millis()) };
         self.last_position = position;
 
         let now = chrono::Utc::now().timestamp_millis();
@@ -271,14 +439,103 @@ impl Player {
         Ok(self)
     }
 
-    pub async fn set_repeat_mode(&mut self, repeat_mode: RepeatMode) -> Result<&mut Self, String> {
+    /// Sends the state of `filter_manager` to the node, replacing all filters applied before
+    pub async fn apply_filters(&mut self) -> Result<&mut Self, LavalinkError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let update_data = LavalinkPlayOptions {
+            filters: Some(self.filter_manager.to_lavalink_filters()),
+            ..Default::default()
+        };
+        self.node.update_player(&self.guild_id, false, &update_data).await?;
+        self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
+        self.filters_sent();
+
+        if self.insta_update_filters_fix_applies() {
+            let position = self.position();
+            self.debug(DebugEvents::PlayerUpdateFilterFixApply, format!("Seeking player {} to {}ms to apply the filters instantly", self.guild_id, position));
+            self.seek(position).await?;
+        }
+
+        Ok(self)
+    }
+
+    /// Lavalink buffers a few seconds of audio, so filter changes are heard late. With `insta_update_filters_fix`
+    /// the player seeks to its position after applying filters, for seekable tracks up to `max_filter_fix_duration`.
+    fn insta_update_filters_fix_applies(&self) -> bool {
+        if !self.options.insta_update_filters_fix.unwrap_or(false) || !self.playing {
+            return false;
+        }
+        let max_duration = self.advanced_options.max_filter_fix_duration.unwrap_or(DEFAULT_MAX_FILTER_FIX_DURATION_MS);
+        self.queue.current.as_ref().is_some_and(|track| {
+            track.info.is_seekable && !track.info.is_stream && track.info.duration > 0 && track.info.duration as u64 <= max_duration
+        })
+    }
+
+    /// Clears every filter including the equalizer and sends that to the node, a volume applied as filter is kept
+    pub async fn reset_filters(&mut self) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.reset();
+        if self.volume_as_filter() {
+            self.filter_manager.set_volume(self.lavalink_volume as f32 / 100.0);
+        }
+        self.apply_filters().await
+    }
+
+    /// Sets the given equalizer bands and applies the filters, see [`FilterManager::set_eq`]
+    pub async fn set_eq(&mut self, bands: Vec<EQBand>) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.set_eq(bands)?;
+        self.apply_filters().await
+    }
+
+    /// Replaces the equalizer with a preset of `EQ_LIST` (e.g. "BassboostHigh", "Rock") and applies the filters
+    pub async fn set_eq_preset(&mut self, name: &str) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.set_eq_preset(name)?;
+        self.apply_filters().await
+    }
+
+    /// Clears the equalizer and applies the filters
+    pub async fn clear_eq(&mut self) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.clear_eq();
+        self.apply_filters().await
+    }
+
+    /// Toggles the nightcore timescale and applies the filters, see [`FilterManager::toggle_nightcore`]
+    pub async fn toggle_nightcore(&mut self, timescale: Option<TimescaleFilter>) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.toggle_nightcore(timescale);
+        self.apply_filters().await
+    }
+
+    /// Toggles the vaporwave timescale and applies the filters, see [`FilterManager::toggle_vaporwave`]
+    pub async fn toggle_vaporwave(&mut self, timescale: Option<TimescaleFilter>) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.toggle_vaporwave(timescale);
+        self.apply_filters().await
+    }
+
+    /// Toggles the rotation (8D) and applies the filters
+    pub async fn toggle_rotation(&mut self, rotation_hz: Option<f32>) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.toggle_rotation(rotation_hz);
+        self.apply_filters().await
+    }
+
+    /// Toggles the karaoke filter and applies the filters
+    pub async fn toggle_karaoke(&mut self, karaoke: Option<KaraokeFilter>) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.toggle_karaoke(karaoke);
+        self.apply_filters().await
+    }
+
+    /// Sets the audio output (mono, stereo, left, right) and applies the filters
+    pub async fn set_audio_output(&mut self, output: AudioOutputs) -> Result<&mut Self, LavalinkError> {
+        self.filter_manager.set_audio_output(output);
+        self.apply_filters().await
+    }
+
+    pub async fn set_repeat_mode(&mut self, repeat_mode: RepeatMode) -> Result<&mut Self, LavalinkError> {
         self.repeat_mode = repeat_mode;
         Ok(self)
     }
 
-    pub async fn skip(&mut self, skip_to: usize, throw_error: bool) -> Result<&mut Self, String> {
+    pub async fn skip(&mut self, skip_to: usize, throw_error: bool) -> Result<&mut Self, LavalinkError> {
         if self.queue.tracks.is_empty() && skip_to > 0 && throw_error {
-            return Err("Can't skip more than the queue size".to_string());
+            return Err(LavalinkError::InvalidArgument("Can't skip more than the queue size".to_string()));
         }
 
         if skip_to > 1 {
@@ -290,10 +547,8 @@ impl Player {
         }
 
         let now = chrono::Utc::now().timestamp_millis();
-        self.set("internal_skipped", Value::Bool(true));
-
         let update_data = LavalinkPlayOptions {
-            track: Some(crate::types::player::TrackPlayOptions {
+            track: Some(TrackPlayOptions {
                 encoded: None,
                 identifier: None,
                 user_data: None,
@@ -304,14 +559,13 @@ impl Player {
         };
         self.node.update_player(&self.guild_id, false, &update_data).await?;
         self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
+        self.mark_track_end("internal_skipped");
 
         Ok(self)
     }
 
-    pub async fn stop_playing(&mut self, clear_queue: bool, execute_autoplay: bool) -> Result<&mut Self, String> {
-        self.set("internal_stopPlaying", Value::Bool(true));
-
-        if self.queue.tracks.len() > 0 && clear_queue {
+    pub async fn stop_playing(&mut self, clear_queue: bool, execute_autoplay: bool) -> Result<&mut Self, LavalinkError> {
+        if !self.queue.tracks.is_empty() && clear_queue {
             let len = self.queue.tracks.len();
             self.queue.splice(0, len, None).await;
         }
@@ -323,7 +577,7 @@ impl Player {
         let now = chrono::Utc::now().timestamp_millis();
 
         let update_data = LavalinkPlayOptions {
-            track: Some(crate::types::player::TrackPlayOptions {
+            track: Some(TrackPlayOptions {
                 encoded: None,
                 identifier: None,
                 user_data: None,
@@ -332,6 +586,7 @@ impl Player {
             ..Default::default()
         };
         self.node.update_player(&self.guild_id, false, &update_data).await?;
+        self.mark_track_end("internal_stopPlaying");
         self.paused = false;
         self.playing = false;
         self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
@@ -339,20 +594,29 @@ impl Player {
         Ok(self)
     }
 
-    pub async fn connect(&mut self) -> Result<&mut Self, String> {
+    /// Tells the manager why the next TrackEndEvent happens. Only set after the node accepted the update and while a track plays,
+    /// otherwise no TrackEndEvent would consume it. The player is locked meanwhile, so the event can't be handled first.
+    fn mark_track_end(&mut self, flag: &str) {
+        self.data.remove("internal_skipped");
+        self.data.remove("internal_stopPlaying");
+        if self.playing {
+            self.set(flag, Value::Bool(true));
+        }
+    }
+
+    pub async fn connect(&mut self) -> Result<&mut Self, LavalinkError> {
         if self.options.voice_channel_id.is_empty() {
-            return Err("No Voice Channel id has been set.".to_string());
+            return Err(LavalinkError::InvalidState("No Voice Channel id has been set.".to_string()));
         }
         
         self.voice_channel_id = Some(self.options.voice_channel_id.clone());
         Ok(self)
     }
 
-    pub async fn change_voice_state(&mut self, voice_channel_id: Option<String>, self_mute: Option<bool>, self_deaf: Option<bool>) -> Result<&mut Self, String> {
-        if let Some(vc) = &voice_channel_id {
-            if Some(vc.clone()) == self.voice_channel_id {
-                return Err("New Channel can't be equal to the old Channel.".to_string());
-            }
+    pub async fn change_voice_state(&mut self, voice_channel_id: Option<String>, self_mute: Option<bool>, self_deaf: Option<bool>) -> Result<&mut Self, LavalinkError> {
+        if let Some(vc) = &voice_channel_id
+            && Some(vc.clone()) == self.voice_channel_id {
+            return Err(LavalinkError::InvalidArgument("New Channel can't be equal to the old Channel.".to_string()));
         }
 
         if let Some(vc) = voice_channel_id.clone() {
@@ -369,15 +633,15 @@ impl Player {
         Ok(self)
     }
 
-    pub async fn disconnect(&mut self, force: bool) -> Result<&mut Self, String> {
+    pub async fn disconnect(&mut self, force: bool) -> Result<&mut Self, LavalinkError> {
         if !force && self.voice_channel_id.is_none() {
-            return Err("No Voice Channel id has been set.".to_string());
+            return Err(LavalinkError::InvalidState("No Voice Channel id has been set.".to_string()));
         }
         self.voice_channel_id = None;
         Ok(self)
     }
 
-    pub async fn destroy(&mut self, reason: Option<String>, disconnect: bool) -> Result<&mut Self, String> {
+    pub async fn destroy(&mut self, reason: Option<String>, disconnect: bool) -> Result<&mut Self, LavalinkError> {
         self.set("internal_destroystatus", Value::Bool(true));
         
         if disconnect {
@@ -387,54 +651,80 @@ impl Player {
         }
 
         self.queue.destroy().await;
-        self.node.destroy_player(&self.guild_id).await?;
+        // A disconnected node has nothing left to destroy
+        if *self.node.connected.read().await {
+            self.node.destroy_player(&self.guild_id).await?;
+        }
+        let _ = self.event_sender.send(ManagerEvent::PlayerDestroy { guild_id: self.guild_id.clone(), reason });
 
         Ok(self)
     }
 
-    pub async fn get_current_lyrics(&self, skip_track_source: bool) -> Result<Option<String>, String> {
+    pub async fn get_current_lyrics(&self, _skip_track_source: bool) -> Result<Option<String>, LavalinkError> {
         Ok(None)
     }
 
-    pub async fn get_lyrics(&self, track: Track, skip_track_source: bool) -> Result<Option<String>, String> {
+    pub async fn get_lyrics(&self, _track: Track, _skip_track_source: bool) -> Result<Option<String>, LavalinkError> {
         Ok(None)
     }
 
     pub fn subscribe_lyrics(&self) { }
     pub fn unsubscribe_lyrics(&self) { }
 
-    pub async fn change_node(&mut self, new_node: Arc<LavalinkNode>, check_sources: bool) -> Result<String, String> {
+    pub async fn change_node(&mut self, new_node: Arc<LavalinkNode>, _check_sources: bool) -> Result<String, LavalinkError> {
         if self.node.id == new_node.id {
-            return Err("Player is already on the provided Node".to_string());
+            return Err(LavalinkError::InvalidArgument("Player is already on the provided Node".to_string()));
         }
-        if let Some(_) = self.get("internal_nodeChanging") {
-            return Err("Player is already changing the node please wait".to_string());
+        if self.get("internal_nodeChanging").is_some() {
+            return Err(LavalinkError::InvalidState("Player is already changing the node please wait".to_string()));
         }
-        
+
         self.set("internal_nodeChanging", Value::Bool(true));
-        if *self.node.connected.read().await {
-            self.node.destroy_player(&self.guild_id).await?;
+        let moved = self.move_to_node(new_node.clone()).await;
+        self.data.remove("internal_nodeChanging");
+        let old_node = moved?;
+
+        let _ = self.event_sender.send(ManagerEvent::PlayerMove { guild_id: self.guild_id.clone(), old_node, new_node: new_node.id.clone() });
+        Ok(new_node.id.clone())
+    }
+
+    /// Recreates the player on the new node and only then destroys it on the old one,
+    /// so the player stays on its old node when the new one doesn't accept it. Returns the old node's id.
+    async fn move_to_node(&mut self, new_node: Arc<LavalinkNode>) -> Result<String, LavalinkError> {
+        // Restored players have no voice channel id, the voice credentials are all the new node needs
+        if self.voice.is_none() {
+            self.connect().await?;
         }
 
-        self.node = new_node.clone();
-        
+        // The new node knows nothing about the player, so send its whole state
         let now = chrono::Utc::now().timestamp_millis();
-        self.connect().await?;
-
-        // re-update player to new node:
         let update_data = LavalinkPlayOptions {
-            position: Some(self.last_position),
-            volume: Some(self.lavalink_volume),
+            track: self.queue.current.as_ref().filter(|t| t.encoded.is_some()).map(|t| TrackPlayOptions {
+                encoded: t.encoded.clone(),
+                identifier: None,
+                user_data: t.user_data.clone(),
+                audio_track_id: None,
+            }),
+            position: Some(self.position()),
+            end_time: self.end_time,
             paused: Some(self.paused),
-            // Track encoded will be correctly fetched and resumed
-            ..Default::default()
+            volume: Some(self.node_volume()),
+            filters: Some(self.filter_manager.to_lavalink_filters()),
+            voice: self.voice.clone(),
         };
-        self.node.update_player(&self.guild_id, false, &update_data).await?;
-
+        new_node.update_player(&self.guild_id, false, &update_data).await?;
         self.ping.lavalink = chrono::Utc::now().timestamp_millis() - now;
-        self.data.remove("internal_nodeChanging");
 
-        Ok(new_node.id.clone())
+        let old_node = std::mem::replace(&mut self.node, new_node);
+        self.filters_sent();
+
+        // The player plays on the new node already, a failure here only leaves a stale player on the old node
+        if *old_node.connected.read().await
+            && let Err(e) = old_node.destroy_player(&self.guild_id).await
+        {
+            warn!("Failed to destroy player {} on its old node {}: {}", self.guild_id, old_node.id, e);
+        }
+        Ok(old_node.id.clone())
     }
 
     pub fn to_json(&mut self) -> serde_json::Value {
@@ -458,3 +748,99 @@ impl Player {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::LavalinkNodeOptions;
+    use crate::types::track::{PluginInfo, SourceNames, TrackInfo};
+    use tokio::sync::mpsc;
+
+    fn test_player() -> Player {
+        let (node_sender, _) = mpsc::channel(1);
+        let node = LavalinkNode::new(LavalinkNodeOptions { id: "test".to_string(), ..Default::default() }, node_sender);
+        let options = PlayerOptions {
+            guild_id: "1".to_string(),
+            voice_channel_id: "2".to_string(),
+            text_channel_id: None,
+            volume: None,
+            vc_region: None,
+            self_deaf: None,
+            self_mute: None,
+            node: None,
+            insta_update_filters_fix: None,
+            apply_volume_as_filter: None,
+            custom_data: None,
+        };
+        Player::new(options, Arc::new(node), broadcast::channel(1).0, true)
+    }
+
+    fn test_track(duration: i64, is_stream: bool) -> Track {
+        Track {
+            encoded: None,
+            info: TrackInfo {
+                identifier: "test".to_string(),
+                title: "Test".to_string(),
+                author: "Test".to_string(),
+                duration,
+                artwork_url: None,
+                uri: None,
+                source_name: SourceNames::Youtube,
+                is_seekable: !is_stream,
+                is_stream,
+                isrc: None,
+            },
+            plugin_info: PluginInfo::default(),
+            requester: None,
+            user_data: None,
+        }
+    }
+
+    /// Pretends the position was last reported `ms_ago` milliseconds ago
+    fn played_for(player: &mut Player, last_position: i64, ms_ago: i64) {
+        player.last_position = last_position;
+        player.last_position_change = Some(chrono::Utc::now().timestamp_millis() - ms_ago);
+    }
+
+    #[test]
+    fn position_follows_the_playback_speed() {
+        let mut player = test_player();
+        played_for(&mut player, 1_000, 10_000);
+        assert!((11_000..11_100).contains(&player.position()));
+
+        player.last_position_speed = 1.5;
+        assert!((16_000..16_150).contains(&player.position()));
+    }
+
+    #[test]
+    fn position_stands_still_while_paused() {
+        let mut player = test_player();
+        played_for(&mut player, 5_000, 10_000);
+        player.paused = true;
+        assert_eq!(player.position(), 5_000);
+
+        player.last_position_change = None;
+        player.paused = false;
+        assert_eq!(player.position(), 5_000);
+    }
+
+    #[test]
+    fn position_is_clamped_to_the_track() {
+        let mut player = test_player();
+        player.queue.current = Some(test_track(20_000, false));
+        played_for(&mut player, 15_000, 60_000);
+        assert_eq!(player.position(), 20_000);
+
+        player.end_time = Some(12_000);
+        assert_eq!(player.position(), 12_000);
+
+        // Streams have no duration to clamp to
+        player.end_time = None;
+        player.queue.current = Some(test_track(20_000, true));
+        assert!(player.position() >= 75_000);
+
+        played_for(&mut player, -500, 0);
+        player.paused = true;
+        assert_eq!(player.position(), 0);
+    }
+}
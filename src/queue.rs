@@ -1,11 +1,10 @@
+use crate::error::LavalinkError;
 use crate::types::queue::{ManagerQueueOptions, QueueStoreManager, QueueChangesWatcher, QueueTrack, DefaultStoredQueue, StoredQueue};
-use crate::types::track::{Track, UnresolvedTrack};
-use crate::utils::ManagerUtils;
+use crate::types::track::Track;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use rand::seq::SliceRandom;
-use std::cmp::Ordering;
 use async_trait::async_trait;
 
 pub struct QueueSaver {
@@ -45,6 +44,12 @@ pub struct DefaultQueueStore {
     data: RwLock<HashMap<String, String>>,
 }
 
+impl Default for DefaultQueueStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DefaultQueueStore {
     pub fn new() -> Self {
         Self {
@@ -155,11 +160,11 @@ impl Queue {
         false
     }
 
-    pub async fn sync(&mut self, override_queue: bool, dont_sync_current: bool) -> Result<(), String> {
+    pub async fn sync(&mut self, override_queue: bool, dont_sync_current: bool) -> Result<(), LavalinkError> {
         if let Some(saver) = &self.queue_saver {
             let data = saver.get(&self.guild_id).await;
             if data.is_none() {
-                return Err(format!("No data found to sync for guildId: {}", self.guild_id));
+                return Err(LavalinkError::InvalidState(format!("No data found to sync for guildId: {}", self.guild_id)));
             }
             let data = data.unwrap();
             
@@ -184,7 +189,7 @@ impl Queue {
             self.save().await;
             Ok(())
         } else {
-            Err("No QueueSaver setup".to_string())
+            Err(LavalinkError::InvalidState("No QueueSaver setup".to_string()))
         }
     }
 
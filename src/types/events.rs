@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use crate::types::track::{LavalinkTrack, PluginInfo};
+use crate::constants::{DebugEvents, DestroyReasons};
+use crate::types::track::{LavalinkTrack, PluginInfo, Track};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -28,6 +29,8 @@ pub enum PlayerEvent {
         guild_id: String,
         exception: Option<Exception>,
         track: LavalinkTrack,
+        // Not sent by Lavalink v4, only by some compatible servers
+        #[serde(default)]
         error: String,
     },
     TrackStuckEvent {
@@ -45,7 +48,50 @@ pub enum PlayerEvent {
         by_remote: bool,
         reason: String,
     },
-    // Add SponsorBlock and Lyrics events if necessary, basic support here
+    /// The SponsorBlock plugin loaded the segments of the current track
+    SegmentsLoaded {
+        #[serde(rename = "guildId")]
+        guild_id: String,
+        segments: Vec<SponsorBlockSegmentData>,
+    },
+    /// The SponsorBlock plugin skipped a segment
+    SegmentSkipped {
+        #[serde(rename = "guildId")]
+        guild_id: String,
+        segment: SponsorBlockSegmentData,
+    },
+    /// The SponsorBlock plugin loaded the chapters of the current track
+    ChaptersLoaded {
+        #[serde(rename = "guildId")]
+        guild_id: String,
+        chapters: Vec<SponsorBlockChapter>,
+    },
+    /// A chapter of the current track started
+    ChapterStarted {
+        #[serde(rename = "guildId")]
+        guild_id: String,
+        chapter: SponsorBlockChapter,
+    },
+}
+
+/// A segment of the SponsorBlock plugin, times are in ms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SponsorBlockSegmentData {
+    /// The category, one of `VALID_SPONSOR_BLOCKS`
+    pub category: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// A chapter of the SponsorBlock plugin, times are in ms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SponsorBlockChapter {
+    pub name: String,
+    pub start: i64,
+    pub end: i64,
+    pub duration: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +128,7 @@ pub struct LavalinkPlayer {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "op")]
+#[allow(clippy::large_enum_variant)]
 pub enum LavalinkMessage {
     #[serde(rename = "ready")]
     Ready {
@@ -101,6 +148,94 @@ pub enum LavalinkMessage {
     Event(PlayerEvent),
 }
 
+impl LavalinkMessage {
+    /// The guild of player related messages
+    pub fn guild_id(&self) -> Option<&str> {
+        match self {
+            Self::Ready { .. } | Self::Stats(_) => None,
+            Self::PlayerUpdate { guild_id, .. } => Some(guild_id),
+            Self::Event(event) => Some(match event {
+                PlayerEvent::TrackStartEvent { guild_id, .. }
+                | PlayerEvent::TrackEndEvent { guild_id, .. }
+                | PlayerEvent::TrackExceptionEvent { guild_id, .. }
+                | PlayerEvent::TrackStuckEvent { guild_id, .. }
+                | PlayerEvent::WebSocketClosedEvent { guild_id, .. }
+                | PlayerEvent::SegmentsLoaded { guild_id, .. }
+                | PlayerEvent::SegmentSkipped { guild_id, .. }
+                | PlayerEvent::ChaptersLoaded { guild_id, .. }
+                | PlayerEvent::ChapterStarted { guild_id, .. } => guild_id,
+            }),
+        }
+    }
+}
+
+/// Messages forwarded from a node's WebSocket task to the manager
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum NodeMessage {
+    /// The WebSocket connection was established
+    Connected { node_id: String },
+    /// The established WebSocket connection was lost
+    Disconnected { node_id: String, reason: String },
+    /// The next reconnect attempt starts after `delay_ms`
+    Reconnecting { node_id: String, attempt: u32, delay_ms: u64 },
+    /// The node gave up reconnecting
+    ReconnectFailed { node_id: String, attempts: u32 },
+    /// The node was destroyed or removed from the manager
+    Destroyed { node_id: String, reason: DestroyReasons },
+    /// Debug information about the node's connection
+    Debug { event: DebugEvents, message: String },
+    /// A message received over the WebSocket
+    Payload { node_id: String, message: LavalinkMessage },
+}
+
+/// Events emitted by the LavalinkManager, the stored players are already updated when they are received
+#[derive(Debug, Clone)]
+pub enum ManagerEvent {
+    /// A node's WebSocket connected
+    NodeConnect { node_id: String },
+    /// A node's WebSocket disconnected
+    NodeDisconnect { node_id: String, reason: String },
+    /// A node will try to reconnect after `delay_ms`
+    NodeReconnecting { node_id: String, attempt: u32, delay_ms: u64 },
+    /// A node gave up reconnecting after `attempts` attempts, its remaining players were destroyed
+    NodeReconnectFailed { node_id: String, attempts: u32 },
+    /// A node was destroyed or removed, its remaining players were destroyed with the same reason
+    NodeDestroy { node_id: String, reason: String },
+    /// A node sent its ready op and has a session
+    NodeReady { node_id: String, session_id: String, resumed: bool },
+    /// Every message received from a node, unprocessed
+    NodeRaw { node_id: String, message: LavalinkMessage },
+    /// A player was created
+    PlayerCreate { guild_id: String },
+    /// A player was destroyed
+    PlayerDestroy { guild_id: String, reason: Option<String> },
+    /// A player was moved to another node
+    PlayerMove { guild_id: String, old_node: String, new_node: String },
+    /// The Discord voice WebSocket of a player was closed
+    PlayerSocketClosed { guild_id: String, code: i32, reason: String, by_remote: bool },
+    /// A track started playing
+    TrackStart { guild_id: String, track: Track },
+    /// A track ended
+    TrackEnd { guild_id: String, track: Option<Track>, reason: String },
+    /// A track got stuck while playing
+    TrackStuck { guild_id: String, track: Option<Track>, threshold_ms: i64 },
+    /// A track threw an exception while playing
+    TrackError { guild_id: String, track: Option<Track>, exception: Option<Exception> },
+    /// The queue of a player ran out of tracks, `track` is the one which ended last
+    QueueEnd { guild_id: String, track: Option<Track> },
+    /// The SponsorBlock plugin loaded the segments of a player's track
+    SegmentsLoaded { guild_id: String, segments: Vec<SponsorBlockSegmentData> },
+    /// The SponsorBlock plugin skipped a segment of a player's track
+    SegmentSkipped { guild_id: String, segment: SponsorBlockSegmentData },
+    /// The SponsorBlock plugin loaded the chapters of a player's track
+    ChaptersLoaded { guild_id: String, chapters: Vec<SponsorBlockChapter> },
+    /// A chapter of a player's track started
+    ChapterStarted { guild_id: String, chapter: SponsorBlockChapter },
+    /// Debug information about what the manager is doing
+    Debug { event: DebugEvents, message: String },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaylistInfoData {
@@ -141,20 +276,17 @@ impl SearchResult {
                 .collect(),
             serde_json::Value::Object(map) => {
                 // If it's a playlist object containing a "tracks" array
-                if let Some(t) = map.get("tracks") {
-                    if let serde_json::Value::Array(arr) = t {
-                        return arr
-                            .iter()
-                            .filter_map(|v| serde_json::from_value(v.clone()).ok())
-                            .collect();
-                    }
+                if let Some(serde_json::Value::Array(arr)) = map.get("tracks") {
+                    return arr
+                        .iter()
+                        .filter_map(|v| serde_json::from_value(v.clone()).ok())
+                        .collect();
                 }
                 
                 // If the data object itself is a single track (Lavalink v4 `loadType="track"`)
-                if self.load_type == "track" {
-                    if let Ok(track) = serde_json::from_value(self.data.clone()) {
-                        return vec![track];
-                    }
+                if self.load_type == "track"
+                    && let Ok(track) = serde_json::from_value(self.data.clone()) {
+                    return vec![track];
                 }
                 
                 vec![]
@@ -1,5 +1,22 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use crate::constants::{AUDIO_OUTPUTS_DATA, EQ_LIST};
+use crate::error::LavalinkError;
+
+/// The highest equalizer band, Lavalink has 15 bands
+pub const MAX_EQ_BAND: u8 = 14;
+/// The gain Lavalink accepts for an equalizer band, 0.0 leaves the band unchanged
+pub const EQ_GAIN_RANGE: RangeInclusive<f32> = -0.25..=1.0;
+
+/// The timescale of [`FilterManager::toggle_nightcore`]
+pub const NIGHTCORE_TIMESCALE: TimescaleFilter = TimescaleFilter { speed: Some(1.289_999_5), pitch: Some(1.289_999_5), rate: Some(0.936_599_95) };
+/// The timescale of [`FilterManager::toggle_vaporwave`]
+pub const VAPORWAVE_TIMESCALE: TimescaleFilter = TimescaleFilter { speed: Some(0.85), pitch: Some(0.8), rate: Some(1.0) };
+/// The rotation of [`FilterManager::toggle_rotation`]
+pub const DEFAULT_ROTATION_HZ: f32 = 0.2;
+/// The karaoke filter of [`FilterManager::toggle_karaoke`]
+pub const DEFAULT_KARAOKE: KaraokeFilter = KaraokeFilter { level: Some(1.0), mono_level: Some(1.0), filter_band: Some(220.0), filter_width: Some(100.0) };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
@@ -60,110 +77,158 @@ pub struct EQBand {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct KaraokeFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub level: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mono_level: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_band: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filter_width: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TimescaleFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub pitch: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rate: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TremoloFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub frequency: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub depth: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct VibratoFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub frequency: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub depth: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct RotationFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rotation_hz: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct DistortionFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sin_offset: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sin_scale: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cos_offset: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cos_scale: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tan_offset: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tan_scale: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub offset: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub scale: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ChannelMixFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub left_to_left: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub left_to_right: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub right_to_left: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub right_to_right: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct NodeLinkEchoFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delay: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub feedback: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mix: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct NodeLinkChorusFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rate: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub depth: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delay: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mix: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub feedback: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct NodeLinkCompressorFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub threshold: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ratio: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub attack: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub release: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gain: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct NodeLinkHighPassFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub smoothing: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct NodeLinkPhaserFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stages: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rate: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub depth: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub feedback: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mix: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_frequency: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_frequency: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct NodeLinkSpatialFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub depth: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rate: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct LowPassFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub smoothing: Option<f32>,
 }
 
@@ -171,59 +236,78 @@ pub struct LowPassFilter {
 #[serde(rename_all = "camelCase")]
 pub struct FilterDataPluginFilters {
     #[serde(rename = "lavalink-filter-plugin")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lavalink_filter_plugin: Option<PluginFilterLavalinkFilter>,
     #[serde(rename = "high-pass")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub high_pass: Option<PluginFilterHighPass>,
     #[serde(rename = "low-pass")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub low_pass: Option<PluginFilterLowPass>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub normalization: Option<PluginFilterNormalization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub echo: Option<PluginFilterEcho>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PluginFilterLavalinkFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub echo: Option<PluginFilterEchoBasic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reverb: Option<PluginFilterReverb>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PluginFilterEchoBasic {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delay: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub decay: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PluginFilterReverb {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delays: Option<Vec<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gains: Option<Vec<f32>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PluginFilterHighPass {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cutoff_frequency: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub boost_factor: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PluginFilterLowPass {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cutoff_frequency: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub boost_factor: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PluginFilterNormalization {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_amplitude: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub adaptive: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PluginFilterEcho {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub echo_length: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub decay: Option<f32>,
 }
 
@@ -246,35 +330,56 @@ pub struct FilterData {
     pub phaser: Option<NodeLinkPhaserFilter>,
     pub spatial: Option<NodeLinkSpatialFilter>,
     pub plugin_filters: Option<FilterDataPluginFilters>,
+    /// The equalizer a node reported, `FilterManager::set_data` moves it to `equalizer_bands`
+    #[serde(default)]
+    pub equalizer: Option<Vec<EQBand>>,
 }
 
+/// The filters object of Lavalink's player update, omitted filters are disabled
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkFilterData {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub volume: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub karaoke: Option<KaraokeFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub timescale: Option<TimescaleFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tremolo: Option<TremoloFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vibrato: Option<VibratoFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rotation: Option<RotationFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distortion: Option<DistortionFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub channel_mix: Option<ChannelMixFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub low_pass: Option<LowPassFilter>,
     #[serde(flatten)]
     pub additional: HashMap<String, serde_json::Value>,
-    // EqBand is passed down but kept separate to easily strip if empty 
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub equalizer: Option<Vec<EQBand>>,
 }
 
-// In rust, FilterManager will likely be a struct tied to the `Player` state or passed to node updates directly.
-// FilterManager logic is moved to `player.rs` where the actual REST updates are made, but for now we provide a stub struct.
+/// The filter state of a player. The setters only change the state, `Player::apply_filters` sends it to the node.
 #[derive(Debug, Clone)]
 pub struct FilterManager {
     pub equalizer_bands: Vec<EQBand>,
+    /// Whether the state changed since it was last sent to the node
     pub filter_updated_state: bool,
+    /// Which filters are active
     pub filters: PlayerFilters,
     pub data: FilterData,
 }
 
+impl Default for FilterManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FilterManager {
     pub fn new() -> Self {
         Self {
@@ -284,4 +389,443 @@ impl FilterManager {
             data: FilterData::default(),
         }
     }
+
+    /// Replaces the whole state, e.g. with the filters a node reported
+    pub fn set_data(&mut self, mut data: FilterData) -> &mut Self {
+        self.equalizer_bands = data.equalizer.take().unwrap_or_default();
+        self.data = data;
+        self.filters.nightcore = false;
+        self.filters.vaporwave = false;
+        self.filters.audio_output = None;
+        self.changed()
+    }
+
+    /// Clears every filter including the equalizer
+    pub fn reset(&mut self) -> &mut Self {
+        self.set_data(FilterData::default())
+    }
+
+    /// Sets the given equalizer bands, bands which are not given keep their gain.
+    /// Fails without changing anything when a band is above 14 or a gain outside -0.25 to 1.0.
+    pub fn set_eq(&mut self, bands: Vec<EQBand>) -> Result<&mut Self, LavalinkError> {
+        if let Some(band) = bands.iter().find(|b| b.band > MAX_EQ_BAND) {
+            return Err(LavalinkError::InvalidArgument(format!("Equalizer band {} doesn't exist, bands are 0 - {}", band.band, MAX_EQ_BAND)));
+        }
+        if let Some(band) = bands.iter().find(|b| !EQ_GAIN_RANGE.contains(&b.gain)) {
+            return Err(LavalinkError::InvalidArgument(format!(
+                "Gain {} of equalizer band {} is outside of {} to {}", band.gain, band.band, EQ_GAIN_RANGE.start(), EQ_GAIN_RANGE.end()
+            )));
+        }
+
+        for band in bands {
+            match self.equalizer_bands.iter_mut().find(|b| b.band == band.band) {
+                Some(existing) => existing.gain = band.gain,
+                None => self.equalizer_bands.push(band),
+            }
+        }
+        self.equalizer_bands.sort_by_key(|b| b.band);
+        Ok(self.changed())
+    }
+
+    /// Sets the gain of one equalizer band
+    pub fn set_eq_band(&mut self, band: u8, gain: f32) -> Result<&mut Self, LavalinkError> {
+        self.set_eq(vec![EQBand { band, gain }])
+    }
+
+    /// Replaces the equalizer with a preset of [`EQ_LIST`], the name is matched case-insensitively.
+    /// Preset gains outside of -0.25 to 1.0 are clamped, Lavalink would clamp them the same way.
+    pub fn set_eq_preset(&mut self, name: &str) -> Result<&mut Self, LavalinkError> {
+        let Some((_, bands)) = EQ_LIST.iter().find(|(preset, _)| preset.eq_ignore_ascii_case(name)) else {
+            return Err(LavalinkError::InvalidArgument(format!("There is no equalizer preset named {}", name)));
+        };
+        let bands = bands.iter()
+            .map(|b| EQBand { band: b.band, gain: b.gain.clamp(*EQ_GAIN_RANGE.start(), *EQ_GAIN_RANGE.end()) })
+            .collect();
+        self.equalizer_bands.clear();
+        self.set_eq(bands)
+    }
+
+    /// Resets every equalizer band to 0.0
+    pub fn clear_eq(&mut self) -> &mut Self {
+        self.equalizer_bands.clear();
+        self.changed()
+    }
+
+    /// The volume filter, 1.0 is the unchanged volume
+    pub fn set_volume(&mut self, volume: f32) -> &mut Self {
+        self.data.volume = Some(volume);
+        self.changed()
+    }
+
+    pub fn clear_volume(&mut self) -> &mut Self {
+        self.data.volume = None;
+        self.changed()
+    }
+
+    pub fn set_karaoke(&mut self, karaoke: KaraokeFilter) -> &mut Self {
+        self.data.karaoke = Some(karaoke);
+        self.changed()
+    }
+
+    pub fn clear_karaoke(&mut self) -> &mut Self {
+        self.data.karaoke = None;
+        self.changed()
+    }
+
+    /// A custom timescale, replaces the nightcore / vaporwave presets
+    pub fn set_timescale(&mut self, timescale: TimescaleFilter) -> &mut Self {
+        self.data.timescale = Some(timescale);
+        self.filters.nightcore = false;
+        self.filters.vaporwave = false;
+        self.changed()
+    }
+
+    pub fn clear_timescale(&mut self) -> &mut Self {
+        self.data.timescale = None;
+        self.filters.nightcore = false;
+        self.filters.vaporwave = false;
+        self.changed()
+    }
+
+    pub fn set_tremolo(&mut self, tremolo: TremoloFilter) -> &mut Self {
+        self.data.tremolo = Some(tremolo);
+        self.changed()
+    }
+
+    pub fn clear_tremolo(&mut self) -> &mut Self {
+        self.data.tremolo = None;
+        self.changed()
+    }
+
+    pub fn set_vibrato(&mut self, vibrato: VibratoFilter) -> &mut Self {
+        self.data.vibrato = Some(vibrato);
+        self.changed()
+    }
+
+    pub fn clear_vibrato(&mut self) -> &mut Self {
+        self.data.vibrato = None;
+        self.changed()
+    }
+
+    pub fn set_rotation(&mut self, rotation: RotationFilter) -> &mut Self {
+        self.data.rotation = Some(rotation);
+        self.changed()
+    }
+
+    pub fn clear_rotation(&mut self) -> &mut Self {
+        self.data.rotation = None;
+        self.changed()
+    }
+
+    pub fn set_distortion(&mut self, distortion: DistortionFilter) -> &mut Self {
+        self.data.distortion = Some(distortion);
+        self.changed()
+    }
+
+    pub fn clear_distortion(&mut self) -> &mut Self {
+        self.data.distortion = None;
+        self.changed()
+    }
+
+    /// Turns the nightcore timescale on or off, `None` uses [`NIGHTCORE_TIMESCALE`].
+    /// Turning it on replaces vaporwave or a custom timescale, turning it off clears the timescale.
+    pub fn toggle_nightcore(&mut self, timescale: Option<TimescaleFilter>) -> &mut Self {
+        if self.filters.nightcore {
+            return self.clear_timescale();
+        }
+        self.data.timescale = Some(timescale.unwrap_or(NIGHTCORE_TIMESCALE));
+        self.filters.nightcore = true;
+        self.filters.vaporwave = false;
+        self.changed()
+    }
+
+    /// Turns the vaporwave timescale on or off, `None` uses [`VAPORWAVE_TIMESCALE`].
+    /// Turning it on replaces nightcore or a custom timescale, turning it off clears the timescale.
+    pub fn toggle_vaporwave(&mut self, timescale: Option<TimescaleFilter>) -> &mut Self {
+        if self.filters.vaporwave {
+            return self.clear_timescale();
+        }
+        self.data.timescale = Some(timescale.unwrap_or(VAPORWAVE_TIMESCALE));
+        self.filters.nightcore = false;
+        self.filters.vaporwave = true;
+        self.changed()
+    }
+
+    /// Turns the rotation (8D) on or off, `None` uses [`DEFAULT_ROTATION_HZ`]
+    pub fn toggle_rotation(&mut self, rotation_hz: Option<f32>) -> &mut Self {
+        if self.data.rotation.is_some() {
+            return self.clear_rotation();
+        }
+        self.set_rotation(RotationFilter { rotation_hz: Some(rotation_hz.unwrap_or(DEFAULT_ROTATION_HZ)) })
+    }
+
+    /// Turns the karaoke filter on or off, `None` uses [`DEFAULT_KARAOKE`]
+    pub fn toggle_karaoke(&mut self, karaoke: Option<KaraokeFilter>) -> &mut Self {
+        if self.data.karaoke.is_some() {
+            return self.clear_karaoke();
+        }
+        self.set_karaoke(karaoke.unwrap_or(DEFAULT_KARAOKE))
+    }
+
+    /// Sets the channel mix of an [`AudioOutputs`] preset, replaces a custom channel mix
+    pub fn set_audio_output(&mut self, output: AudioOutputs) -> &mut Self {
+        self.data.channel_mix = AUDIO_OUTPUTS_DATA.get(&output).cloned();
+        self.filters.audio_output = Some(output);
+        self.changed()
+    }
+
+    /// A custom channel mix, replaces the audio output preset
+    pub fn set_channel_mix(&mut self, channel_mix: ChannelMixFilter) -> &mut Self {
+        self.data.channel_mix = Some(channel_mix);
+        self.filters.audio_output = None;
+        self.changed()
+    }
+
+    pub fn clear_channel_mix(&mut self) -> &mut Self {
+        self.data.channel_mix = None;
+        self.filters.audio_output = None;
+        self.changed()
+    }
+
+    pub fn set_low_pass(&mut self, low_pass: LowPassFilter) -> &mut Self {
+        self.data.low_pass = Some(low_pass);
+        self.changed()
+    }
+
+    pub fn clear_low_pass(&mut self) -> &mut Self {
+        self.data.low_pass = None;
+        self.changed()
+    }
+
+    /// NodeLink's echo filter
+    pub fn set_echo(&mut self, echo: NodeLinkEchoFilter) -> &mut Self {
+        self.data.echo = Some(echo);
+        self.changed()
+    }
+
+    pub fn clear_echo(&mut self) -> &mut Self {
+        self.data.echo = None;
+        self.changed()
+    }
+
+    /// NodeLink's chorus filter
+    pub fn set_chorus(&mut self, chorus: NodeLinkChorusFilter) -> &mut Self {
+        self.data.chorus = Some(chorus);
+        self.changed()
+    }
+
+    pub fn clear_chorus(&mut self) -> &mut Self {
+        self.data.chorus = None;
+        self.changed()
+    }
+
+    /// NodeLink's compressor filter
+    pub fn set_compressor(&mut self, compressor: NodeLinkCompressorFilter) -> &mut Self {
+        self.data.compressor = Some(compressor);
+        self.changed()
+    }
+
+    pub fn clear_compressor(&mut self) -> &mut Self {
+        self.data.compressor = None;
+        self.changed()
+    }
+
+    /// NodeLink's high pass filter
+    pub fn set_high_pass(&mut self, high_pass: NodeLinkHighPassFilter) -> &mut Self {
+        self.data.high_pass = Some(high_pass);
+        self.changed()
+    }
+
+    pub fn clear_high_pass(&mut self) -> &mut Self {
+        self.data.high_pass = None;
+        self.changed()
+    }
+
+    /// NodeLink's phaser filter
+    pub fn set_phaser(&mut self, phaser: NodeLinkPhaserFilter) -> &mut Self {
+        self.data.phaser = Some(phaser);
+        self.changed()
+    }
+
+    pub fn clear_phaser(&mut self) -> &mut Self {
+        self.data.phaser = None;
+        self.changed()
+    }
+
+    /// NodeLink's spatial filter
+    pub fn set_spatial(&mut self, spatial: NodeLinkSpatialFilter) -> &mut Self {
+        self.data.spatial = Some(spatial);
+        self.changed()
+    }
+
+    pub fn clear_spatial(&mut self) -> &mut Self {
+        self.data.spatial = None;
+        self.changed()
+    }
+
+    /// Clears NodeLink's echo, chorus, compressor, high pass, phaser and spatial filters
+    pub fn clear_node_link_filters(&mut self) -> &mut Self {
+        self.data.echo = None;
+        self.data.chorus = None;
+        self.data.compressor = None;
+        self.data.high_pass = None;
+        self.data.phaser = None;
+        self.data.spatial = None;
+        self.changed()
+    }
+
+    /// Filters of the lavalink-filter-plugin and LavaDSPX
+    pub fn set_plugin_filters(&mut self, plugin_filters: FilterDataPluginFilters) -> &mut Self {
+        self.data.plugin_filters = Some(plugin_filters);
+        self.changed()
+    }
+
+    pub fn clear_plugin_filters(&mut self) -> &mut Self {
+        self.data.plugin_filters = None;
+        self.changed()
+    }
+
+    // Marks the state as changed and derives the flags of `filters` from it.
+    // nightcore, vaporwave and audio_output are presets and kept by the methods setting them.
+    fn changed(&mut self) -> &mut Self {
+        let data = &self.data;
+        let filters = &mut self.filters;
+        filters.custom = data.timescale.is_some() && !filters.nightcore && !filters.vaporwave;
+        filters.rotation = data.rotation.is_some();
+        filters.karaoke = data.karaoke.is_some();
+        filters.tremolo = data.tremolo.is_some();
+        filters.vibrato = data.vibrato.is_some();
+        filters.low_pass = data.low_pass.is_some();
+        filters.volume = data.volume.is_some_and(|v| v != 1.0);
+        filters.node_link_echo = data.echo.is_some();
+        filters.node_link_chorus = data.chorus.is_some();
+        filters.node_link_compressor = data.compressor.is_some();
+        filters.node_link_high_pass = data.high_pass.is_some();
+        filters.node_link_phaser = data.phaser.is_some();
+        filters.node_link_spatial = data.spatial.is_some();
+
+        let plugin_filters = data.plugin_filters.as_ref();
+        let filter_plugin = plugin_filters.and_then(|p| p.lavalink_filter_plugin.as_ref());
+        filters.lavalink_filter_plugin.echo = filter_plugin.is_some_and(|f| f.echo.is_some());
+        filters.lavalink_filter_plugin.reverb = filter_plugin.is_some_and(|f| f.reverb.is_some());
+        filters.lavalink_lava_dspx_plugin.low_pass = plugin_filters.is_some_and(|p| p.low_pass.is_some());
+        filters.lavalink_lava_dspx_plugin.high_pass = plugin_filters.is_some_and(|p| p.high_pass.is_some());
+        filters.lavalink_lava_dspx_plugin.normalization = plugin_filters.is_some_and(|p| p.normalization.is_some());
+        filters.lavalink_lava_dspx_plugin.echo = plugin_filters.is_some_and(|p| p.echo.is_some());
+
+        self.filter_updated_state = true;
+        self
+    }
+
+    /// How fast tracks play with the current timescale, its speed times its rate
+    pub fn playback_speed(&self) -> f64 {
+        self.data.timescale.as_ref().map_or(1.0, |t| t.speed.unwrap_or(1.0) as f64 * t.rate.unwrap_or(1.0) as f64)
+    }
+
+    /// The full filter state in the shape Lavalink expects, plugin and NodeLink filters are sent as additional fields
+    pub fn to_lavalink_filters(&self) -> LavalinkFilterData {
+        let data = &self.data;
+        let mut additional = HashMap::new();
+        let extras = [
+            ("echo", serde_json::to_value(&data.echo)),
+            ("chorus", serde_json::to_value(&data.chorus)),
+            ("compressor", serde_json::to_value(&data.compressor)),
+            ("highPass", serde_json::to_value(&data.high_pass)),
+            ("phaser", serde_json::to_value(&data.phaser)),
+            ("spatial", serde_json::to_value(&data.spatial)),
+            ("pluginFilters", serde_json::to_value(&data.plugin_filters)),
+        ];
+        for (key, value) in extras {
+            if let Ok(value) = value
+                && !value.is_null() {
+                additional.insert(key.to_string(), value);
+            }
+        }
+
+        LavalinkFilterData {
+            volume: data.volume,
+            karaoke: data.karaoke.clone(),
+            timescale: data.timescale.clone(),
+            tremolo: data.tremolo.clone(),
+            vibrato: data.vibrato.clone(),
+            rotation: data.rotation.clone(),
+            distortion: data.distortion.clone(),
+            channel_mix: data.channel_mix.clone(),
+            low_pass: data.low_pass.clone(),
+            additional,
+            equalizer: (!self.equalizer_bands.is_empty()).then(|| self.equalizer_bands.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_eq_merges_bands() {
+        let mut filters = FilterManager::new();
+        filters.set_eq(vec![EQBand { band: 3, gain: 0.2 }, EQBand { band: 0, gain: 0.1 }]).unwrap();
+        filters.set_eq_band(3, -0.1).unwrap();
+
+        let bands: Vec<_> = filters.equalizer_bands.iter().map(|b| (b.band, b.gain)).collect();
+        assert_eq!(bands, [(0, 0.1), (3, -0.1)]);
+        assert!(filters.filter_updated_state);
+    }
+
+    #[test]
+    fn set_eq_rejects_invalid_bands() {
+        let mut filters = FilterManager::new();
+        filters.set_eq_band(1, 0.5).unwrap();
+
+        assert!(filters.set_eq(vec![EQBand { band: 2, gain: 0.1 }, EQBand { band: 15, gain: 0.1 }]).is_err());
+        assert!(filters.set_eq_band(2, -0.3).is_err());
+        assert!(filters.set_eq_band(2, 1.1).is_err());
+        assert!(filters.set_eq_band(14, 1.0).is_ok());
+
+        // Nothing of a rejected call is applied
+        let bands: Vec<_> = filters.equalizer_bands.iter().map(|b| (b.band, b.gain)).collect();
+        assert_eq!(bands, [(1, 0.5), (14, 1.0)]);
+    }
+
+    #[test]
+    fn set_eq_preset_replaces_and_clamps() {
+        let mut filters = FilterManager::new();
+        filters.set_eq_band(2, 0.9).unwrap();
+        filters.set_eq_preset("gaming").unwrap();
+
+        assert_eq!(filters.equalizer_bands.len(), EQ_LIST["Gaming"].len());
+        assert!(filters.equalizer_bands.iter().all(|b| EQ_GAIN_RANGE.contains(&b.gain)));
+        let band_14 = filters.equalizer_bands.iter().find(|b| b.band == 14).unwrap();
+        assert_eq!(band_14.gain, -0.25);
+
+        assert!(filters.set_eq_preset("unknown").is_err());
+        filters.clear_eq();
+        assert!(filters.equalizer_bands.is_empty());
+    }
+
+    #[test]
+    fn nightcore_and_vaporwave_exclude_each_other() {
+        let mut filters = FilterManager::new();
+        filters.toggle_nightcore(None);
+        assert!(filters.filters.nightcore);
+        assert_eq!(filters.data.timescale.as_ref().and_then(|t| t.speed), NIGHTCORE_TIMESCALE.speed);
+
+        filters.toggle_vaporwave(None);
+        assert!(filters.filters.vaporwave && !filters.filters.nightcore);
+        assert_eq!(filters.data.timescale.as_ref().and_then(|t| t.speed), VAPORWAVE_TIMESCALE.speed);
+
+        // Toggling the active one off clears the timescale
+        filters.toggle_vaporwave(None);
+        assert!(!filters.filters.vaporwave && !filters.filters.nightcore);
+        assert!(filters.data.timescale.is_none());
+
+        // A custom timescale replaces the preset, the next toggle turns nightcore on again
+        filters.toggle_nightcore(None);
+        filters.set_timescale(TimescaleFilter { speed: Some(1.1), pitch: None, rate: None });
+        assert!(!filters.filters.nightcore);
+        filters.toggle_nightcore(None);
+        assert!(filters.filters.nightcore);
+        assert_eq!(filters.data.timescale.as_ref().and_then(|t| t.speed), NIGHTCORE_TIMESCALE.speed);
+    }
 }
@@ -1,7 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::types::queue::ManagerQueueOptions;
-use crate::types::track::Track;
 
 /// The Bot client Options needed for the manager
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,7 +20,7 @@ pub struct BotClientOptions {
 pub struct ManagerPlayerOptions {
     /// If the Lavalink Volume should be decremented by x number
     pub volume_decrementer: Option<f64>,
-    /// How often it should update the the player Position
+    /// How often (in ms) the estimated player positions are moved into `last_position`, disabled when `None` or 0
     pub client_based_position_update_interval: Option<u64>,
     /// What should be used as a searchPlatform, if no source was provided during the query
     pub default_search_platform: Option<String>,
@@ -97,7 +96,7 @@ pub struct ManagerOptions {
     pub advanced_options: Option<AdvancedOptions>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct AdvancedOptions {
     /// Max duration for that the filter fix duration works (in ms) - default is 8mins
@@ -108,7 +107,7 @@ pub struct AdvancedOptions {
     pub debug_options: Option<DebugOptions>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct DebugOptions {
     /// For logging custom searches
@@ -1,32 +1,23 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use async_trait::async_trait;
 use crate::types::track::PluginInfo;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LavalinkNodeOptions {
-    pub node_type: Option<String>,
-    pub host: String,
-    pub port: u16,
-    pub authorization: String,
-    pub secure: Option<bool>,
-    pub session_id: Option<String>,
-    pub id: Option<String>,
-    pub regions: Option<Vec<String>>,
-    pub retry_amount: Option<i32>,
-    pub retry_delay: Option<i32>,
-    pub retry_timespan: Option<i32>,
-    pub request_signal_timeout_ms: Option<i32>,
-    pub close_on_error: Option<bool>,
-    pub heart_beat_interval: Option<i32>,
-    pub enable_ping_on_stats_check: Option<bool>,
-    pub auto_checks: Option<LavalinkNodeAutoChecks>,
+/// Persists the Lavalink session id of each node, so sessions can be resumed after a restart
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    async fn get(&self, node_id: &str) -> Option<String>;
+    async fn set(&self, node_id: &str, session_id: &str) -> bool;
+    async fn delete(&self, node_id: &str) -> bool;
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Which checks run against the node's cached info before searching, both default to `true`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkNodeAutoChecks {
+    /// Reject requests needing a plugin the node didn't load, e.g. LavaSearch or `speak:`
     pub plugin_validations: Option<bool>,
+    /// Reject sources and links whose source manager is not enabled on the node, e.g. `amsearch` without `applemusic`
     pub sources_validations: Option<bool>,
 }
 
@@ -53,17 +44,6 @@ pub struct FrameStats {
     pub deficit: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BaseNodeStats {
-    pub players: i32,
-    pub playing_players: i32,
-    pub uptime: i64,
-    pub memory: MemoryStats,
-    pub cpu: CPUStats,
-    pub frame_stats: FrameStats,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NodeLinkConnectionMetrics {
@@ -88,30 +68,81 @@ pub struct NodeLinkConnectionMetricsSpeed {
     pub mbps: f64,
 }
 
+/// The stats op of a node, NodeLink additionally sends `detailed_stats`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NodeStats {
-    #[serde(flatten)]
-    pub base: BaseNodeStats,
+    pub players: i32,
+    pub playing_players: i32,
+    pub uptime: i64,
+    pub memory: MemoryStats,
+    pub cpu: CPUStats,
+    /// Missing when the node has no playing players
+    #[serde(default)]
+    pub frame_stats: Option<FrameStats>,
+    #[serde(default)]
     pub detailed_stats: Option<HashMap<String, serde_json::Value>>,
 }
 
+impl NodeStats {
+    /// The average amount of frames which were not sent per minute, 0 when the node doesn't report frame stats
+    pub fn frame_deficit(&self) -> i64 {
+        self.frame_stats.as_ref().map(|f| f.deficit.unwrap_or(0) + f.nulled.unwrap_or(0)).unwrap_or(0)
+    }
+
+    /// Lavalink's penalty score, a lower score means a less loaded node.
+    /// Combines playing players, system cpu load and the deficit / nulled frames.
+    pub fn penalties(&self) -> f64 {
+        let player_penalty = self.playing_players as f64;
+        let cpu_penalty = 1.05f64.powf(100.0 * self.cpu.system_load) * 10.0 - 10.0;
+
+        let (deficit_frame_penalty, null_frame_penalty) = match &self.frame_stats {
+            Some(frames) => (
+                1.03f64.powf(500.0 * (frames.deficit.unwrap_or(0) as f64 / 3000.0)) * 600.0 - 600.0,
+                (1.03f64.powf(500.0 * (frames.nulled.unwrap_or(0) as f64 / 3000.0)) * 300.0 - 300.0) * 2.0,
+            ),
+            None => (0.0, 0.0),
+        };
+
+        player_penalty + cpu_penalty + deficit_frame_penalty + null_frame_penalty
+    }
+}
+
+/// A stats op with the time it was received
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatsSnapshot {
+    /// Unix timestamp in ms
+    pub received_at: i64,
+    pub stats: NodeStats,
+}
+
+/// The response of `/v4/info`. Everything but the version may be left out, NodeLink doesn't send all of it
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkInfo {
     pub version: VersionObject,
+    /// Unix timestamp in ms
+    #[serde(default)]
     pub build_time: i64,
+    #[serde(default)]
     pub git: GitObject,
+    #[serde(default)]
     pub jvm: String,
+    #[serde(default)]
     pub lavaplayer: String,
+    #[serde(default)]
     pub source_managers: Vec<String>,
+    #[serde(default)]
     pub filters: Vec<String>,
+    #[serde(default)]
     pub plugins: Vec<PluginObject>,
+    #[serde(default)]
     pub is_nodelink: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct VersionObject {
     pub semver: String,
     pub major: i32,
@@ -121,12 +152,13 @@ pub struct VersionObject {
     pub build: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct GitObject {
     pub branch: String,
     pub commit: String,
-    pub commit_time: String,
+    /// Unix timestamp in ms
+    pub commit_time: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -153,11 +185,110 @@ pub struct LyricsLine {
     pub plugin: PluginInfo,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What a node's connection task is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ReconnectionState {
+    /// Connected, or not connecting anymore
     Idle,
+    /// Waiting for the next reconnect attempt
     Reconnecting,
+    /// Connecting to the WebSocket
     Pending,
     Destroying,
 }
+
+/// The server software of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeType {
+    Lavalink,
+    NodeLink,
+}
+
+/// Features which not every node supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeCapability {
+    /// Mixer layers on top of the playing track (NodeLink)
+    Mixer,
+    /// Echo, chorus, compressor, high pass, phaser and spatial filters (NodeLink)
+    NodeLinkFilters,
+    /// Lyrics and chapters of the playing track (NodeLink)
+    NodeLinkLyrics,
+    /// The `/v4/connection` metrics (NodeLink)
+    ConnectionMetrics,
+    /// Direct track streams (NodeLink)
+    DirectStream,
+    /// Switching the audio track language (NodeLink)
+    AudioTrackLanguage,
+    /// The YouTube config and OAuth routes (NodeLink)
+    YoutubeConfig,
+    /// LavaSearch's loadsearch endpoint, needs `lavasearch-plugin`
+    LavaSearch,
+    /// Lyrics via `lavalyrics-plugin`
+    LavaLyrics,
+    /// SponsorBlock segments, needs `sponsorblock-plugin`
+    SponsorBlock,
+}
+
+impl NodeCapability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mixer => "Mixer",
+            Self::NodeLinkFilters => "NodeLinkFilters",
+            Self::NodeLinkLyrics => "NodeLinkLyrics",
+            Self::ConnectionMetrics => "ConnectionMetrics",
+            Self::DirectStream => "DirectStream",
+            Self::AudioTrackLanguage => "AudioTrackLanguage",
+            Self::YoutubeConfig => "YoutubeConfig",
+            Self::LavaSearch => "LavaSearch",
+            Self::LavaLyrics => "LavaLyrics",
+            Self::SponsorBlock => "SponsorBlock",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_lavalink_info() {
+        // As sent by Lavalink 4.0.8
+        let body = r#"{
+            "version": { "semver": "4.0.8", "major": 4, "minor": 0, "patch": 8, "preRelease": null, "build": null },
+            "buildTime": 1727381566624,
+            "git": { "branch": "HEAD", "commit": "2ba5abd", "commitTime": 1727381392000 },
+            "jvm": "18.0.2.1",
+            "lavaplayer": "2.2.2",
+            "sourceManagers": ["youtube", "soundcloud", "bandcamp", "twitch", "vimeo", "http", "spotify", "applemusic", "deezer"],
+            "filters": ["volume", "equalizer", "karaoke", "timescale", "tremolo", "vibrato", "distortion", "rotation", "channelMix", "lowPass"],
+            "plugins": [
+                { "name": "youtube-plugin", "version": "1.8.3" },
+                { "name": "lavasrc-plugin", "version": "4.2.0" },
+                { "name": "sponsorblock-plugin", "version": "3.0.1" }
+            ]
+        }"#;
+
+        let info: LavalinkInfo = serde_json::from_str(body).unwrap();
+        assert_eq!(info.version.semver, "4.0.8");
+        assert_eq!(info.git.commit_time, 1727381392000);
+        assert!(info.source_managers.iter().any(|s| s == "applemusic"));
+        assert_eq!(info.plugins[2].name, "sponsorblock-plugin");
+        assert_eq!(info.is_nodelink, None);
+    }
+
+    #[test]
+    fn deserializes_partial_node_link_info() {
+        let body = r#"{
+            "version": { "semver": "3.0.0", "major": 3, "minor": 0, "patch": 0 },
+            "isNodelink": true,
+            "sourceManagers": ["youtube", "soundcloud"],
+            "plugins": []
+        }"#;
+
+        let info: LavalinkInfo = serde_json::from_str(body).unwrap();
+        assert_eq!(info.is_nodelink, Some(true));
+        assert_eq!(info.git.commit_time, 0);
+        assert!(info.filters.is_empty());
+    }
+}
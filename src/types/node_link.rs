@@ -89,6 +89,7 @@ pub struct MixEndedEvent {
     pub reason: String,
 }
 
+/// Upper bounds of each performance level, values above `poor` are unhealthy
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthStatusThreshold {
     pub excellent: f64,
@@ -97,6 +98,28 @@ pub struct HealthStatusThreshold {
     pub poor: f64,
 }
 
+impl HealthStatusThreshold {
+    /// Default thresholds of the lavalink and system cpu load (0.0 - 1.0)
+    pub const CPU: Self = Self { excellent: 0.3, good: 0.5, fair: 0.7, poor: 0.85 };
+    /// Default thresholds of the memory usage (0.0 - 1.0 of the reservable memory)
+    pub const MEMORY: Self = Self { excellent: 0.5, good: 0.7, fair: 0.85, poor: 0.95 };
+    /// Default thresholds of the WebSocket ping in ms
+    pub const PING: Self = Self { excellent: 50.0, good: 100.0, fair: 200.0, poor: 300.0 };
+
+    /// The performance level of a value: `excellent`, `good`, `fair` or `poor`
+    pub fn performance(&self, value: f64) -> &'static str {
+        if value <= self.excellent {
+            "excellent"
+        } else if value <= self.good {
+            "good"
+        } else if value <= self.fair {
+            "fair"
+        } else {
+            "poor"
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct HealthStatusThresholdOptions {
     pub cpu: Option<HealthStatusThreshold>,
@@ -109,6 +132,7 @@ pub struct HealthStatusThresholdOptions {
 pub struct NodeMetricSummary {
     pub cpu_load: f64,
     pub system_load: f64,
+    /// Used memory in percent of the reservable memory
     pub memory_usage: f64,
     pub players: i32,
     pub playing_players: i32,
@@ -120,16 +144,37 @@ pub struct NodeMetricSummary {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct HealthStatusObject {
-    pub status: String, // HealthStatusKeys
-    pub performance: String, // HealthPerformanceKeys
+    /// `healthy`, `degraded`, `unhealthy` or `offline`
+    pub status: String,
+    /// `excellent`, `good`, `fair` or `poor`, the worst level of cpu, memory and ping
+    pub performance: String,
     pub is_overloaded: bool,
     pub needs_restart: bool,
+    /// Lavalink's penalty score, see `NodeStats::penalties`
     pub penalty_score: f64,
+    /// Capacity left in percent, by the higher one of cpu load and memory usage
     pub estimated_remaining_capacity: f64,
     pub recommendations: Vec<String>,
     pub metrics: NodeMetricSummary,
 }
 
+/// The health of every node of a manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeHealthSummary {
+    pub total_nodes: usize,
+    pub healthy_nodes: usize,
+    pub degraded_nodes: usize,
+    pub unhealthy_nodes: usize,
+    pub offline_nodes: usize,
+    pub overloaded_nodes: usize,
+    pub total_players: i64,
+    pub total_playing_players: i64,
+    /// Average penalty score of the online nodes
+    pub average_penalty_score: f64,
+    pub nodes: std::collections::HashMap<String, HealthStatusObject>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AddMixerLayerResponse {
@@ -1,6 +1,8 @@
-use crate::types::filters::{FilterData, EQBand, LavalinkFilterData};
-use super::{track::{Track, UnresolvedTrack}, queue::StoredQueue};
+use crate::types::filters::LavalinkFilterData;
+use crate::constants::VALID_SPONSOR_BLOCKS;
+use crate::error::LavalinkError;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -10,6 +12,51 @@ pub enum RepeatMode {
     Off,
 }
 
+/// A SponsorBlock category, see [`VALID_SPONSOR_BLOCKS`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SponsorBlockSegment {
+    Sponsor,
+    SelfPromo,
+    Interaction,
+    Intro,
+    Outro,
+    Preview,
+    #[serde(rename = "music_offtopic")]
+    MusicOfftopic,
+    Filler,
+}
+
+impl SponsorBlockSegment {
+    pub const ALL: [Self; 8] = [
+        Self::Sponsor, Self::SelfPromo, Self::Interaction, Self::Intro,
+        Self::Outro, Self::Preview, Self::MusicOfftopic, Self::Filler,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sponsor => "sponsor",
+            Self::SelfPromo => "selfpromo",
+            Self::Interaction => "interaction",
+            Self::Intro => "intro",
+            Self::Outro => "outro",
+            Self::Preview => "preview",
+            Self::MusicOfftopic => "music_offtopic",
+            Self::Filler => "filler",
+        }
+    }
+}
+
+impl FromStr for SponsorBlockSegment {
+    type Err = LavalinkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|segment| segment.as_str() == s).ok_or_else(|| LavalinkError::InvalidArgument(format!(
+            "Invalid SponsorBlock category \"{}\", valid ones are {}", s, VALID_SPONSOR_BLOCKS.join(", ")
+        )))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlayerPing {
@@ -57,12 +104,19 @@ pub struct TrackPlayOptions {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkPlayOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub track: Option<TrackPlayOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub end_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub paused: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub volume: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filters: Option<LavalinkFilterData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub voice: Option<LavalinkPlayerVoiceOptions>,
 }
 
@@ -78,7 +132,7 @@ pub struct PlayOptions {
     pub no_replace: Option<bool>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct VoiceState {
     pub self_deaf: bool,
     pub self_mute: bool,
@@ -87,14 +141,25 @@ pub struct VoiceState {
     pub suppress: bool,
 }
 
-impl Default for VoiceState {
-    fn default() -> Self {
-        Self {
-            self_deaf: false,
-            self_mute: false,
-            server_deaf: false,
-            server_mute: false,
-            suppress: false,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sponsor_block_categories_match_the_plugin() {
+        // PUT /sponsorblock/categories takes the categories as a JSON string array
+        let body = serde_json::to_string(&[SponsorBlockSegment::SelfPromo, SponsorBlockSegment::MusicOfftopic]).unwrap();
+        assert_eq!(body, r#"["selfpromo","music_offtopic"]"#);
+
+        // and GET returns them the same way
+        let categories: Vec<String> = serde_json::from_str(r#"["sponsor","music_offtopic","chapter"]"#).unwrap();
+        let segments: Vec<SponsorBlockSegment> = categories.iter().filter_map(|c| c.parse().ok()).collect();
+        assert_eq!(segments, [SponsorBlockSegment::Sponsor, SponsorBlockSegment::MusicOfftopic]);
+
+        for segment in SponsorBlockSegment::ALL {
+            assert_eq!(serde_json::to_value(segment).unwrap(), segment.as_str());
         }
+        // VALID_SPONSOR_BLOCKS lists the same categories
+        assert_eq!(SponsorBlockSegment::ALL.map(|segment| segment.as_str()), VALID_SPONSOR_BLOCKS);
     }
 }
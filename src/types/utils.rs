@@ -123,19 +123,27 @@ pub struct GuildShardPayloadData {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavaSearchFilteredResponse {
-    pub info: PlaylistInfo,
+    pub info: crate::types::events::PlaylistInfoData,
+    #[serde(default)]
     pub plugin_info: PluginInfo,
-    pub tracks: Vec<Track>,
+    #[serde(default)]
+    pub tracks: Vec<crate::types::track::LavalinkTrack>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavaSearchResponse {
-    pub tracks: Vec<Track>,
+    #[serde(default)]
+    pub tracks: Vec<crate::types::track::LavalinkTrack>,
+    #[serde(default)]
     pub albums: Vec<LavaSearchFilteredResponse>,
+    #[serde(default)]
     pub artists: Vec<LavaSearchFilteredResponse>,
+    #[serde(default)]
     pub playlists: Vec<LavaSearchFilteredResponse>,
+    #[serde(default)]
     pub texts: Vec<LavaSearchTextResult>,
+    #[serde(default)]
     pub plugin_info: PluginInfo,
 }
 
@@ -143,6 +151,7 @@ pub struct LavaSearchResponse {
 #[serde(rename_all = "camelCase")]
 pub struct LavaSearchTextResult {
     pub text: String,
+    #[serde(default)]
     pub plugin_info: PluginInfo,
 }
 
@@ -1,10 +1,14 @@
-use crate::types::track::{LavalinkTrack, Track, UnresolvedTrack, TrackInfo, PluginInfo, UnresolvedTrackInfo, TrackRequester, SourceNames};
-use crate::types::events::{SearchResult, Exception};
-use crate::types::player::PlayerOptions;
-use crate::manager::LavalinkManagerOptions;
+use crate::types::track::{LavalinkTrack, Track, UnresolvedTrack, PluginInfo, UnresolvedTrackInfo, TrackRequester};
+use crate::error::LavalinkError;
 use crate::node::LavalinkNode;
-use crate::statics::{DEFAULT_SOURCES, YOUTUBE_REGEX, SOUNDCLOUD_REGEX, BANDCAMP_REGEX, TWITCH_TV_REGEX, VIMEO_REGEX, TIKTOK_REGEX, MIXCLOUD_REGEX, ALL_SPOTIFY_REGEX, APPLE_MUSIC_REGEX, ALL_DEEZER_REGEX, MUSIC_YANDEX_REGEX, JIOSAAVN_REGEX, TIDAL_REGEX, ALL_PANDORA_REGEX, YOUTUBE_MUSIC_REGEX, SOUNDCLOUD_MOBILE_REGEX};
+use crate::statics::*;
+use crate::constants::LavalinkPlugins;
+use crate::types::node::LavalinkInfo;
 use url::Url;
+use tracing::debug;
+
+/// The search platform used when a query has no source
+pub const DEFAULT_SEARCH_PLATFORM: &str = "ytsearch";
 
 /// Parses Node Connection Url: "lavalink://<nodeId>:<nodeAuthorization(Password)>@<NodeHost>:<NodePort>"
 pub fn parse_lavalink_conn_url(connection_url: &str) -> Result<LavalinkConnUrl, url::ParseError> {
@@ -20,6 +24,22 @@ pub fn parse_lavalink_conn_url(connection_url: &str) -> Result<LavalinkConnUrl,
     })
 }
 
+/// Derives the Discord voice region from a voice server endpoint:
+/// "us-east1234.discord.media:443" -> "us-east", "c-fra09-1a2b3c4d.discord.media:443" -> "fra"
+pub fn voice_region_from_endpoint(endpoint: &str) -> Option<String> {
+    let host = endpoint.trim_start_matches("wss://").split([':', '/']).next()?;
+    let label = host.split('.').next()?;
+    let region = match label.strip_prefix("c-") {
+        Some(rest) => rest.split('-').next()?,
+        None => label,
+    };
+    let region = region.trim_end_matches(|c: char| c.is_ascii_digit()).trim_end_matches('-');
+    if region.is_empty() {
+        return None;
+    }
+    Some(region.to_lowercase())
+}
+
 #[derive(Debug, Clone)]
 pub struct LavalinkConnUrl {
     pub id: String,
@@ -28,6 +48,7 @@ pub struct LavalinkConnUrl {
     pub port: u16,
 }
 
+#[derive(Debug, Clone)]
 pub struct ManagerUtils {
     pub default_search_platform: String,
 }
@@ -58,9 +79,9 @@ impl ManagerUtils {
         info
     }
 
-    pub fn build_track(&self, data: LavalinkTrack, requester_id: Option<String>) -> Result<Track, String> {
+    pub fn build_track(&self, data: LavalinkTrack, requester_id: Option<String>) -> Result<Track, LavalinkError> {
         if data.encoded.is_none() {
-            return Err("Argument 'data.encoded' must be present.".to_string());
+            return Err(LavalinkError::InvalidArgument("Argument 'data.encoded' must be present.".to_string()));
         }
         
         let requester = requester_id.map(|id| TrackRequester { id: Some(id) });
@@ -76,9 +97,9 @@ impl ManagerUtils {
         Ok(track)
     }
 
-    pub fn build_unresolved_track(&self, title: Option<String>, uri: Option<String>, encoded: Option<String>, requester_id: Option<String>) -> Result<UnresolvedTrack, String> {
+    pub fn build_unresolved_track(&self, title: Option<String>, uri: Option<String>, encoded: Option<String>, requester_id: Option<String>) -> Result<UnresolvedTrack, LavalinkError> {
         if title.is_none() && encoded.is_none() && uri.is_none() {
-            return Err("Argument 'query' must be present.".to_string());
+            return Err(LavalinkError::InvalidArgument("Argument 'query' must be present.".to_string()));
         }
         
         let requester = requester_id.map(|id| TrackRequester { id: Some(id) });
@@ -96,7 +117,7 @@ impl ManagerUtils {
         })
     }
 
-    pub fn is_node(&self, node: &LavalinkNode) -> bool {
+    pub fn is_node(&self, _node: &LavalinkNode) -> bool {
         // Rust's strong typing guarantees this is a Node, but we keep the method signature for TS matching
         true
     }
@@ -116,26 +137,54 @@ impl ManagerUtils {
         !data.info.title.is_empty() || data.encoded.is_some()
     }
 
-    pub fn validate_query_string(&self, node: &LavalinkNode, query_string: &str, source_string: Option<&str>) -> Result<(), String> {
+    /// Validates a query before searching it. Links are rejected when their source manager is not enabled on the node,
+    /// which is skipped while the node's info is not fetched yet or `auto_checks.sources_validations` is disabled.
+    pub async fn validate_query_string(&self, node: &LavalinkNode, query_string: &str, source_string: Option<&str>) -> Result<(), LavalinkError> {
         if query_string.trim().is_empty() {
-            return Err("Query string is empty, please provide a valid query string.".to_string());
+            return Err(LavalinkError::InvalidQuery("Query string is empty, please provide a valid query string.".to_string()));
         }
 
-        if let Some(src) = source_string {
-            if src == "speak" && query_string.len() > 100 {
-                return Err("Query is speak, which is limited to 100 characters.".to_string());
-            }
+        if let Some(src) = source_string
+            && src == "speak" && query_string.len() > 100 {
+            return Err(LavalinkError::InvalidQuery("Query is speak, which is limited to 100 characters.".to_string()));
         }
 
         if !query_string.starts_with("http://") && !query_string.starts_with("https://") {
             return Ok(());
         }
 
-        // The checks for source presence are omitted if we don't know the node's cached supported sources yet.
-        // Assuming `node` has `info` available in a full implementation:
-        /*
-        if YOUTUBE_MUSIC_REGEX.is_match(query_string) || YOUTUBE_REGEX.is_match(query_string) { ... }
-        */
+        if !Self::sources_validations(node) {
+            return Ok(());
+        }
+        Self::load_info(node).await;
+        let info = node.info.read().await;
+        let Some(info) = info.as_ref() else {
+            return Ok(());
+        };
+
+        let link_sources: [(bool, &str); 14] = [
+            (YOUTUBE_MUSIC_REGEX.is_match(query_string) || YOUTUBE_REGEX.is_match(query_string), "youtube"),
+            (SOUNDCLOUD_REGEX.is_match(query_string) || SOUNDCLOUD_MOBILE_REGEX.is_match(query_string), "soundcloud"),
+            (BANDCAMP_REGEX.is_match(query_string), "bandcamp"),
+            (TWITCH_TV_REGEX.is_match(query_string), "twitch"),
+            (VIMEO_REGEX.is_match(query_string), "vimeo"),
+            (TIKTOK_REGEX.is_match(query_string), "tiktok"),
+            (MIXCLOUD_REGEX.is_match(query_string), "mixcloud"),
+            (ALL_SPOTIFY_REGEX.is_match(query_string), "spotify"),
+            (APPLE_MUSIC_REGEX.is_match(query_string), "applemusic"),
+            (ALL_DEEZER_REGEX.is_match(query_string), "deezer"),
+            (MUSIC_YANDEX_REGEX.is_match(query_string), "yandexmusic"),
+            (JIOSAAVN_REGEX.is_match(query_string), "jiosaavn"),
+            (TIDAL_REGEX.is_match(query_string), "tidal"),
+            (ALL_PANDORA_REGEX.is_match(query_string), "pandora"),
+        ];
+        for (matches, source_manager) in link_sources {
+            if matches && !Self::has_source_manager(info, source_manager) {
+                return Err(LavalinkError::InvalidQuery(format!(
+                    "Query / Link Provided for this Source but Lavalink Node has not '{}' enabled", source_manager
+                )));
+            }
+        }
 
         Ok(())
     }
@@ -144,33 +193,51 @@ impl ManagerUtils {
         let lower_query = query_string.to_lowercase();
         for (source, _mapped) in DEFAULT_SOURCES.iter() {
             let prefix = format!("{}:", source);
-            if lower_query.starts_with(&prefix) {
-                if !["https", "http"].contains(source) {
-                    return Some(source.to_string());
-                }
+            if lower_query.starts_with(&prefix) && !["https", "http"].contains(source) {
+                return Some(source.to_string());
             }
         }
         None
     }
 
-    pub fn extract_source_of_query(&self, mut search_query: SearchQuery) -> SearchQuery {
-        if let Some(found_source) = self.find_source_of_query(&search_query.query) {
-            if let Some(mapped) = DEFAULT_SOURCES.get(found_source.as_str()) {
-                search_query.source = Some(mapped.to_string());
-                let prefix_len = found_source.len() + 1; // "source:"
-                search_query.query = search_query.query[prefix_len..].to_string();
+    /// A `source:` prefix this client doesn't know, e.g. `amzsearch` of a plugin. Links are no prefixes
+    pub fn find_unknown_source_of_query(&self, query_string: &str) -> Option<String> {
+        let (prefix, _) = query_string.split_once(':')?;
+        if prefix.is_empty() || ["https", "http"].contains(&prefix)
+            || !prefix.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()) {
+            return None;
+        }
+        Some(prefix.to_string())
+    }
+
+    /// Splits a known or unknown `source:` prefix off the query, known ones are mapped to their search platform
+    fn split_source_of_query(&self, query: &str) -> Option<(String, String)> {
+        let (source, mapped) = match self.find_source_of_query(query) {
+            Some(found_source) => {
+                let mapped = DEFAULT_SOURCES.get(found_source.as_str())?.to_string();
+                (found_source, mapped)
             }
+            None => {
+                let found_source = self.find_unknown_source_of_query(query)?;
+                (found_source.clone(), found_source)
+            }
+        };
+        let prefix_len = source.len() + 1; // "source:"
+        Some((mapped, query[prefix_len..].to_string()))
+    }
+
+    pub fn extract_source_of_query(&self, mut search_query: SearchQuery) -> SearchQuery {
+        if let Some((source, query)) = self.split_source_of_query(&search_query.query) {
+            search_query.source = Some(source);
+            search_query.query = query;
         }
         search_query
     }
 
     pub fn extract_source_of_lava_query(&self, mut search_query: LavaSearchQuery) -> LavaSearchQuery {
-        if let Some(found_source) = self.find_source_of_query(&search_query.query) {
-            if let Some(mapped) = DEFAULT_SOURCES.get(found_source.as_str()) {
-                search_query.source = Some(mapped.to_string());
-                let prefix_len = found_source.len() + 1; // "source:"
-                search_query.query = search_query.query[prefix_len..].to_string();
-            }
+        if let Some((source, query)) = self.split_source_of_query(&search_query.query) {
+            search_query.source = Some(source);
+            search_query.query = query;
         }
         search_query
     }
@@ -206,12 +273,137 @@ impl ManagerUtils {
         })
     }
 
-    pub fn validate_source_string(&self, node: &LavalinkNode, source_string: &str) -> Result<(), String> {
-        let source_lower = source_string.to_lowercase();
-        let _source = DEFAULT_SOURCES.get(source_lower.as_str());
+    /// Validates a search source, e.g. `amsearch` needs the `applemusic` source manager and `speak` the DuncteBot plugin.
+    /// The checks are skipped while the node's info is not fetched yet, and follow the node's `auto_checks`.
+    /// Sources this client doesn't know, e.g. of other plugins, are passed through.
+    pub async fn validate_source_string(&self, node: &LavalinkNode, source_string: &str) -> Result<(), LavalinkError> {
+        let source_lower = source_string.trim().to_lowercase();
+        let source = DEFAULT_SOURCES.get(source_lower.as_str()).copied().unwrap_or(source_lower.as_str());
+
+        Self::load_info(node).await;
+        let info = node.info.read().await;
+        let Some(info) = info.as_ref() else {
+            return Ok(());
+        };
+
+        if Self::sources_validations(node) {
+            let source_manager = match source {
+                "amsearch" => Some("applemusic"),
+                "dzsearch" | "dzisrc" | "dzrec" => Some("deezer"),
+                "scsearch" => Some("soundcloud"),
+                "ytsearch" | "ytmsearch" => Some("youtube"),
+                "spsearch" | "sprec" => Some("spotify"),
+                "ymsearch" | "ymrec" => Some("yandexmusic"),
+                "vksearch" | "vkrec" => Some("vkmusic"),
+                "qbsearch" | "qbisrc" | "qbrec" => Some("qobuz"),
+                "pdsearch" | "pdisrc" | "pdrec" => Some("pandora"),
+                "tdsearch" | "tdrec" => Some("tidal"),
+                "jssearch" | "jsrec" => Some("jiosaavn"),
+                "bcsearch" => Some("bandcamp"),
+                "phsearch" => Some("pornhub"),
+                "local" => Some("local"),
+                "http" | "https" | "link" | "uri" => Some("http"),
+                _ => None,
+            };
+            if let Some(source_manager) = source_manager
+                && !Self::has_source_manager(info, source_manager) {
+                return Err(LavalinkError::InvalidQuery(format!(
+                    "Lavalink Node has not '{}' enabled, which is required to have '{}' work", source_manager, source
+                )));
+            }
+            if source == "ftts" && !Self::has_source_manager(info, "ftts") && !Self::has_source_manager(info, "flowery-tts") && !Self::has_plugin(info, LavalinkPlugins::LAVA_SRC) {
+                return Err(LavalinkError::InvalidQuery(
+                    "Lavalink Node has not 'flowery-tts' enabled, which is required to have 'ftts' work".to_string()
+                ));
+            }
+        }
+
+        if Self::plugin_validations(node) {
+            let plugin = match source {
+                "speak" => Some(LavalinkPlugins::DUNCTE_BOT),
+                "tts" => Some(LavalinkPlugins::GOOGLE_CLOUD_TTS),
+                _ => None,
+            };
+            if let Some(plugin) = plugin
+                && !Self::has_plugin(info, plugin) {
+                return Err(LavalinkError::InvalidQuery(format!(
+                    "Lavalink Node has not the '{}' loaded, which is required to have '{}' work", plugin, source
+                )));
+            }
+        }
 
-        // We would validate node.info.source_managers here if available
-        // e.g. if _source.unwrap() == "amsearch" && !node.info.source_managers.contains("applemusic") Error!
         Ok(())
     }
+
+    /// Fails when the node's info doesn't list the plugin, e.g. [`LavalinkPlugins::LAVA_SEARCH`] before a LavaSearch request.
+    /// Skipped while the node's info is not fetched yet or `auto_checks.plugin_validations` is disabled.
+    pub async fn validate_plugin(&self, node: &LavalinkNode, plugin: &str) -> Result<(), LavalinkError> {
+        if !Self::plugin_validations(node) {
+            return Ok(());
+        }
+        Self::load_info(node).await;
+        match node.info.read().await.as_ref() {
+            Some(info) if !Self::has_plugin(info, plugin) => Err(LavalinkError::InvalidQuery(format!(
+                "There is no {} available in the Lavalink Node: {}", plugin, node.id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Loads the node's info if needed, the validations are skipped when it can't be fetched
+    async fn load_info(node: &LavalinkNode) {
+        if let Err(e) = node.load_info().await {
+            debug!("Skipping the validations of node {}, its info can't be fetched: {}", node.id, e);
+        }
+    }
+
+    fn sources_validations(node: &LavalinkNode) -> bool {
+        node.options.auto_checks.as_ref().and_then(|c| c.sources_validations).unwrap_or(true)
+    }
+
+    fn plugin_validations(node: &LavalinkNode) -> bool {
+        node.options.auto_checks.as_ref().and_then(|c| c.plugin_validations).unwrap_or(true)
+    }
+
+    fn has_source_manager(info: &LavalinkInfo, source_manager: &str) -> bool {
+        info.source_managers.iter().any(|s| s.eq_ignore_ascii_case(source_manager))
+    }
+
+    fn has_plugin(info: &LavalinkInfo, plugin: &str) -> bool {
+        info.plugins.iter().any(|p| p.name.eq_ignore_ascii_case(plugin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_voice_regions() {
+        assert_eq!(voice_region_from_endpoint("us-east1234.discord.media:443").as_deref(), Some("us-east"));
+        assert_eq!(voice_region_from_endpoint("rotterdam42.discord.media").as_deref(), Some("rotterdam"));
+        assert_eq!(voice_region_from_endpoint("c-fra09-1a2b3c4d.discord.media:443").as_deref(), Some("fra"));
+        assert_eq!(voice_region_from_endpoint("wss://us-west567.discord.media:443/?v=8").as_deref(), Some("us-west"));
+        assert_eq!(voice_region_from_endpoint("wss://c-sin01-a1b2.discord.media").as_deref(), Some("sin"));
+        assert_eq!(voice_region_from_endpoint(""), None);
+        assert_eq!(voice_region_from_endpoint("1234.discord.media:443"), None);
+    }
+
+    #[test]
+    fn keeps_unknown_search_prefixes() {
+        let utils = ManagerUtils::new(DEFAULT_SEARCH_PLATFORM.to_string());
+        let query = utils.transform_query(SearchQuery { query: "amzsearch:foo".to_string(), source: None });
+        assert_eq!(query.source.as_deref(), Some("amzsearch"));
+        assert_eq!(query.query, "foo");
+
+        let query = utils.transform_query(SearchQuery { query: "spotify:foo".to_string(), source: None });
+        assert_eq!(query.source.as_deref(), Some("spsearch"));
+
+        let query = utils.transform_query(SearchQuery { query: "https://youtu.be/dQw4w9WgXcQ".to_string(), source: None });
+        assert_eq!(query.source.as_deref(), Some(DEFAULT_SEARCH_PLATFORM));
+        assert_eq!(query.query, "https://youtu.be/dQw4w9WgXcQ");
+
+        let query = utils.transform_query(SearchQuery { query: "Artist: Song".to_string(), source: None });
+        assert_eq!(query.source.as_deref(), Some(DEFAULT_SEARCH_PLATFORM));
+    }
 }